@@ -0,0 +1,80 @@
+// Times both `--concurrency-model`s on a synthetic batch of recipes, some of
+// them with slow pkgver scripts so the work is uneven. Run with
+// `cargo bench -p sbuild-linter`; FILES, JOBS and RUNS override the size.
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use tempfile::TempDir;
+
+fn var(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn recipe(index: usize) -> String {
+    // one file in ten takes a while, as pkgver scripts fetching a release do
+    let pkgver = if index.is_multiple_of(10) {
+        "sleep 0.2; echo 1.0"
+    } else {
+        "echo 1.0"
+    };
+    format!(
+        "#!/SBUILD
+_disabled: false
+pkg: bench{index}
+description: Synthetic package {index}
+src_url:
+  - https://example.com/bench{index}
+x_exec:
+  shell: sh
+  pkgver: {pkgver}
+  run: echo {index}
+"
+    )
+}
+
+fn time_model(model: &str, files: &[PathBuf], jobs: usize, runs: usize) -> Duration {
+    let mut total = Duration::ZERO;
+    for _ in 0..runs {
+        let start = Instant::now();
+        let status = Command::new(env!("CARGO_BIN_EXE_sbuild-linter"))
+            .args(["--no-shellcheck", "--pkgver", "--no-summary"])
+            .args(["--concurrency-model", model])
+            .args(["--parallel", &jobs.to_string()])
+            .args(files)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .expect("Failed to run sbuild-linter");
+        total += start.elapsed();
+        assert!(status.success(), "{} run failed", model);
+    }
+    total / runs as u32
+}
+
+fn main() {
+    let count = var("FILES", 200);
+    let jobs = var("JOBS", 4);
+    let runs = var("RUNS", 3).max(1);
+
+    let dir = TempDir::new().unwrap();
+    let files: Vec<PathBuf> = (0..count)
+        .map(|index| {
+            let path = dir.path().join(format!("bench{}.yaml", index));
+            fs::write(&path, recipe(index)).unwrap();
+            path
+        })
+        .collect();
+
+    println!("{} files, {} jobs, mean of {} run(s)", count, jobs, runs);
+    for model in ["threadpool", "rayon"] {
+        println!("{:>10}: {:?}", model, time_model(model, &files, jobs, runs));
+    }
+}
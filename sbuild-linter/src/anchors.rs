@@ -0,0 +1,135 @@
+use indexmap::IndexMap;
+
+#[derive(Debug, Default)]
+pub struct Anchors {
+    pub defined: IndexMap<String, usize>,
+    pub aliases: IndexMap<String, usize>,
+}
+
+impl Anchors {
+    // Anchors (`&name`) and aliases (`*name`) are collected from the node
+    // properties of each line. Block scalars (`run: |`) are skipped so that
+    // shell syntax like `cmd &` or `*.tar.gz` inside scripts is ignored.
+    pub fn parse(yaml_str: &str) -> Self {
+        let mut anchors = Anchors::default();
+        let mut block_indent: Option<usize> = None;
+
+        for (index, line) in yaml_str.lines().enumerate() {
+            let line_number = index + 1;
+            let indent = line.len() - line.trim_start().len();
+
+            if let Some(parent_indent) = block_indent {
+                if line.trim().is_empty() || indent > parent_indent {
+                    continue;
+                }
+                block_indent = None;
+            }
+
+            let (properties, content) = scan_line(line);
+            for (marker, name) in properties {
+                let target = if marker == '&' {
+                    &mut anchors.defined
+                } else {
+                    &mut anchors.aliases
+                };
+                target.entry(name).or_insert(line_number);
+            }
+
+            if starts_block_scalar(content) {
+                block_indent = Some(indent);
+            }
+        }
+
+        anchors
+    }
+
+    pub fn unused(&self) -> impl Iterator<Item = (&String, &usize)> {
+        self.defined
+            .iter()
+            .filter(|(name, _)| !self.aliases.contains_key(*name))
+    }
+
+    pub fn undefined(&self) -> impl Iterator<Item = (&String, &usize)> {
+        self.aliases
+            .iter()
+            .filter(|(name, _)| !self.defined.contains_key(*name))
+    }
+}
+
+// Returns the node properties found on the line along with the line
+// content up to any trailing comment.
+fn scan_line(line: &str) -> (Vec<(char, String)>, &str) {
+    let mut properties = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    let mut quote = None;
+    let mut node_start = true;
+    let mut flow_depth = 0usize;
+    let mut prev: Option<char> = None;
+
+    while let Some((index, c)) = chars.next() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            prev = Some(c);
+            continue;
+        }
+
+        let next_is_space = chars.peek().is_none_or(|&(_, n)| n.is_whitespace());
+        if c == '#' && prev.is_none_or(char::is_whitespace) {
+            return (properties, &line[..index]);
+        } else if c.is_whitespace() {
+        } else if (c == ':' && next_is_space) || (flow_depth > 0 && c == ',') {
+            node_start = true;
+        } else if flow_depth > 0 && (c == ']' || c == '}') {
+            flow_depth -= 1;
+            node_start = false;
+        } else if node_start {
+            match c {
+                '-' | '?' if next_is_space => {}
+                '[' | '{' => flow_depth += 1,
+                '"' | '\'' => {
+                    quote = Some(c);
+                    node_start = false;
+                }
+                '&' | '*' => {
+                    let mut name = String::new();
+                    while let Some(&(_, n)) = chars.peek() {
+                        if n.is_whitespace() || ",[]{}".contains(n) {
+                            break;
+                        }
+                        name.push(n);
+                        chars.next();
+                    }
+                    if !name.is_empty() {
+                        properties.push((c, name));
+                    }
+                }
+                _ => node_start = false,
+            }
+        }
+        prev = Some(c);
+    }
+
+    (properties, line)
+}
+
+fn starts_block_scalar(content: &str) -> bool {
+    let tokens: Vec<&str> = content.split_whitespace().collect();
+    let Some((last, rest)) = tokens.split_last() else {
+        return false;
+    };
+
+    let is_indicator = last.starts_with(['|', '>'])
+        && last[1..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '+' || c == '-');
+    if !is_indicator {
+        return false;
+    }
+
+    match rest.iter().rev().find(|t| !t.starts_with(['&', '!'])) {
+        None => true,
+        Some(prev) => prev.ends_with(':') || *prev == "-" || *prev == "?",
+    }
+}
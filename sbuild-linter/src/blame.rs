@@ -0,0 +1,73 @@
+use std::{collections::HashMap, path::Path, process::Command};
+
+pub struct LineBlame {
+    pub author: String,
+    pub commit: String,
+}
+
+impl LineBlame {
+    pub fn is_committed(&self) -> bool {
+        !self.commit.bytes().all(|b| b == b'0')
+    }
+}
+
+// Who last changed each line of a file, according to `git blame`.
+pub struct Blame {
+    lines: Vec<LineBlame>,
+}
+
+impl Blame {
+    // Returns None if git isn't installed or the file isn't tracked in a
+    // repository.
+    pub fn for_file(file_path: &str) -> Option<Self> {
+        let path = Path::new(file_path);
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["blame", "--porcelain", "--"])
+            .arg(path.file_name()?)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        Some(Blame {
+            lines: parse_porcelain(&String::from_utf8_lossy(&output.stdout)),
+        })
+    }
+
+    // `line_number` starts at 1, like the lines of the file.
+    pub fn line(&self, line_number: usize) -> Option<&LineBlame> {
+        self.lines.get(line_number.checked_sub(1)?)
+    }
+}
+
+// The porcelain format only lists the details of a commit the first time it
+// shows up, so the authors are remembered by commit.
+fn parse_porcelain(output: &str) -> Vec<LineBlame> {
+    let mut authors: HashMap<String, String> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut commit = String::new();
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            lines.push(LineBlame {
+                author: authors.get(&commit).cloned().unwrap_or_default(),
+                commit: commit.clone(),
+            });
+        } else if let Some(author) = line.strip_prefix("author ") {
+            authors.insert(commit.clone(), author.to_string());
+        } else if let Some(sha) = line.split(' ').next() {
+            if sha.len() == 40 && sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+                commit = sha.to_string();
+            }
+        }
+    }
+
+    lines
+}
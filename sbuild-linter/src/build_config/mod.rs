@@ -1,9 +1,6 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter, Write},
-};
+use std::io::{self, Write};
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use serde::Deserialize;
 use serde_yml::Value;
 
@@ -16,13 +13,14 @@ use crate::{
     license::{License, LicenseComplex},
     resource::Resource,
     xexec::XExec,
-    BuildAsset,
+    BuildAsset, DEFAULT_FIELD_ORDER,
 };
 
 pub mod visitor;
 
 #[derive(Debug, Default)]
 pub struct BuildConfig {
+    pub format_version: Option<u64>,
     pub _disabled: bool,
     pub _disabled_reason: Option<DisabledReason>,
     pub pkg: String,
@@ -77,11 +75,12 @@ impl BuildConfig {
             })
         };
 
+        config.format_version = values.get("format_version").and_then(Value::as_u64);
         config._disabled = values.get("_disabled").unwrap().as_bool().unwrap();
         if let Some(val) = values.get("_disabled_reason") {
             if let Some(str_val) = val.as_str() {
                 config._disabled_reason = Some(DisabledReason::Simple(str_val.to_string()));
-            } else if let Some(_) = val.as_sequence() {
+            } else if val.as_sequence().is_some() {
                 config._disabled_reason =
                     Some(DisabledReason::List(to_string_vec(val).unwrap_or_default()));
             } else if let Some(map_val) = val.as_mapping() {
@@ -227,177 +226,146 @@ impl BuildConfig {
         config
     }
 
-    pub fn write_yaml(
+    pub fn write_yaml<W: Write>(
         &self,
-        writer: &mut BufWriter<File>,
+        writer: &mut W,
         indent: usize,
         comments: Comments,
     ) -> io::Result<()> {
-        let indent_str = " ".repeat(indent);
+        let order: Vec<&str> = DEFAULT_FIELD_ORDER.split(',').collect();
+        self.write_yaml_ordered(writer, indent, comments, &order)
+    }
 
+    // Writes the fields in the given order. Fields missing from it follow in
+    // the default order, so none are dropped.
+    pub fn write_yaml_ordered<W: Write, S: AsRef<str>>(
+        &self,
+        writer: &mut W,
+        indent: usize,
+        comments: Comments,
+        order: &[S],
+    ) -> io::Result<()> {
         for c in &comments.header_comments {
             writeln!(writer, "{}", c)?;
         }
 
-        let write_field_comments = |writer: &mut BufWriter<File>, field: &str| -> io::Result<()> {
+        let mut fields: IndexSet<&str> = order.iter().map(AsRef::as_ref).collect();
+        fields.extend(DEFAULT_FIELD_ORDER.split(','));
+        for field in fields {
             if let Some(comments) = comments.field_comments.get(field) {
                 for comment in comments {
                     writeln!(writer, "{}", comment)?;
                 }
             }
-            Ok(())
-        };
-
-        write_field_comments(writer, "_disabled")?;
-        writeln!(writer, "{}_disabled: {}\n", indent_str, self._disabled)?;
-
-        write_field_comments(writer, "_disabled_reason")?;
-        if let Some(ref value) = self._disabled_reason {
-            value.write_yaml(writer, indent)?;
-        }
-
-        write_field_comments(writer, "pkg")?;
-        writeln!(writer, "{}pkg: \"{}\"", indent_str, self.pkg)?;
-
-        write_field_comments(writer, "pkg_id")?;
-        writeln!(writer, "{}pkg_id: \"{}\"", indent_str, self.pkg_id)?;
-
-        write_field_comments(writer, "pkg_type")?;
-        if let Some(ref pkg_type) = self.pkg_type {
-            writeln!(writer, "{}pkg_type: \"{}\"", indent_str, pkg_type)?;
+            self.write_field(writer, field, indent)?;
         }
 
-        write_field_comments(writer, "pkgver")?;
-        if let Some(ref pkgver) = self.pkgver {
-            writeln!(writer, "{}pkgver: \"{}\"", indent_str, pkgver)?;
-        }
+        Ok(())
+    }
 
-        write_field_comments(writer, "app_id")?;
-        if let Some(ref app_id) = self.app_id {
-            writeln!(writer, "{}app_id: \"{}\"", indent_str, app_id)?;
-        }
+    fn write_field<W: Write>(&self, writer: &mut W, field: &str, indent: usize) -> io::Result<()> {
+        let indent_str = " ".repeat(indent);
 
-        write_field_comments(writer, "build_util")?;
-        if let Some(ref build_util) = self.build_util {
-            writeln!(writer, "{}build_util:", indent_str)?;
-            for util in build_util {
-                writeln!(writer, "{}  - \"{}\"", indent_str, util)?;
+        let write_list = |writer: &mut W, values: &[String]| -> io::Result<()> {
+            writeln!(writer, "{}{}:", indent_str, field)?;
+            for value in values {
+                writeln!(writer, "{}  - \"{}\"", indent_str, value)?;
             }
-        }
+            Ok(())
+        };
 
-        write_field_comments(writer, "build_asset")?;
-        if let Some(ref build_asset) = self.build_asset {
-            writeln!(writer, "{}build_asset:", indent_str)?;
-            for asset in build_asset {
-                writeln!(writer, "{}  - url: \"{}\"", indent_str, asset.url)?;
-                writeln!(writer, "{}    out: \"{}\"", indent_str, asset.out)?;
+        match field {
+            "format_version" => {
+                if let Some(format_version) = self.format_version {
+                    writeln!(writer, "{}format_version: {}", indent_str, format_version)?;
+                }
             }
-        }
-
-        write_field_comments(writer, "category")?;
-        writeln!(writer, "{}category:", indent_str)?;
-        for cat in &self.category {
-            writeln!(writer, "{}  - \"{}\"", indent_str, cat)?;
-        }
-
-        write_field_comments(writer, "description")?;
-        self.description
-            .clone()
-            .unwrap()
-            .write_yaml(writer, indent)?;
-
-        write_field_comments(writer, "distro_pkg")?;
-        if let Some(ref distro_pkg) = self.distro_pkg {
-            writeln!(writer, "{}distro_pkg:", indent_str)?;
-            distro_pkg.write_yaml(writer, indent)?;
-        }
-
-        write_field_comments(writer, "homepage")?;
-        if let Some(ref homepage) = self.homepage {
-            writeln!(writer, "{}homepage:", indent_str)?;
-            for url in homepage {
-                writeln!(writer, "{}  - \"{}\"", indent_str, url)?;
+            "_disabled" => writeln!(writer, "{}_disabled: {}\n", indent_str, self._disabled)?,
+            "_disabled_reason" => {
+                if let Some(ref value) = self._disabled_reason {
+                    value.write_yaml(writer, indent)?;
+                }
             }
-        }
-
-        write_field_comments(writer, "maintainer")?;
-        if let Some(ref maintainer) = self.maintainer {
-            writeln!(writer, "{}maintainer:", indent_str)?;
-            for m in maintainer {
-                writeln!(writer, "{}  - \"{}\"", indent_str, m)?;
+            "pkg" => writeln!(writer, "{}pkg: \"{}\"", indent_str, self.pkg)?,
+            "pkg_id" => writeln!(writer, "{}pkg_id: \"{}\"", indent_str, self.pkg_id)?,
+            "pkg_type" => {
+                if let Some(ref pkg_type) = self.pkg_type {
+                    writeln!(writer, "{}pkg_type: \"{}\"", indent_str, pkg_type)?;
+                }
             }
-        }
-
-        write_field_comments(writer, "icon")?;
-        if let Some(ref icon) = self.icon {
-            writeln!(writer, "{}icon:", indent_str)?;
-            icon.write_yaml(writer, indent)?;
-        }
-
-        write_field_comments(writer, "desktop")?;
-        if let Some(ref desktop) = self.desktop {
-            writeln!(writer, "{}desktop:", indent_str)?;
-            desktop.write_yaml(writer, indent)?;
-        }
-
-        write_field_comments(writer, "license")?;
-        if let Some(ref license) = self.license {
-            writeln!(writer, "{}license:", indent_str)?;
-            for lc in license {
-                lc.write_yaml(writer, indent)?;
+            "pkgver" => {
+                if let Some(ref pkgver) = self.pkgver {
+                    writeln!(writer, "{}pkgver: \"{}\"", indent_str, pkgver)?;
+                }
             }
-        }
-
-        if let Some(ref build_asset) = self.build_asset {
-            writeln!(writer, "{}build_asset:", indent_str)?;
-            for asset in build_asset {
-                writeln!(writer, "{}  - url: \"{}\"", indent_str, asset.url)?;
-                writeln!(writer, "{}    out: \"{}\"", indent_str, asset.out)?;
+            "app_id" => {
+                if let Some(ref app_id) = self.app_id {
+                    writeln!(writer, "{}app_id: \"{}\"", indent_str, app_id)?;
+                }
             }
-        }
-
-        write_field_comments(writer, "note")?;
-        if let Some(ref note) = self.note {
-            writeln!(writer, "{}note:", indent_str)?;
-            for n in note {
-                writeln!(writer, "{}  - \"{}\"", indent_str, n)?;
+            "build_asset" => {
+                if let Some(ref build_asset) = self.build_asset {
+                    writeln!(writer, "{}build_asset:", indent_str)?;
+                    for asset in build_asset {
+                        writeln!(writer, "{}  - url: \"{}\"", indent_str, asset.url)?;
+                        writeln!(writer, "{}    out: \"{}\"", indent_str, asset.out)?;
+                    }
+                }
             }
-        }
-
-        write_field_comments(writer, "provides")?;
-        if let Some(ref provides) = self.provides {
-            writeln!(writer, "{}provides:", indent_str)?;
-            for p in provides {
-                writeln!(writer, "{}  - \"{}\"", indent_str, p)?;
+            "category" => write_list(writer, &self.category)?,
+            "description" => self
+                .description
+                .clone()
+                .unwrap()
+                .write_yaml(writer, indent)?,
+            "distro_pkg" => {
+                if let Some(ref distro_pkg) = self.distro_pkg {
+                    writeln!(writer, "{}distro_pkg:", indent_str)?;
+                    distro_pkg.write_yaml(writer, indent)?;
+                }
             }
-        }
-
-        write_field_comments(writer, "repology")?;
-        if let Some(ref repology) = self.repology {
-            writeln!(writer, "{}repology:", indent_str)?;
-            for r in repology {
-                writeln!(writer, "{}  - \"{}\"", indent_str, r)?;
+            "icon" => {
+                if let Some(ref icon) = self.icon {
+                    writeln!(writer, "{}icon:", indent_str)?;
+                    icon.write_yaml(writer, indent)?;
+                }
             }
-        }
-
-        write_field_comments(writer, "src_url")?;
-        writeln!(writer, "{}src_url:", indent_str)?;
-        for url in &self.src_url {
-            writeln!(writer, "{}  - \"{}\"", indent_str, url)?;
-        }
-
-        write_field_comments(writer, "tag")?;
-        if let Some(ref tag) = self.tag {
-            writeln!(writer, "{}tag:", indent_str)?;
-            for t in tag {
-                writeln!(writer, "{}  - \"{}\"", indent_str, t)?;
+            "desktop" => {
+                if let Some(ref desktop) = self.desktop {
+                    writeln!(writer, "{}desktop:", indent_str)?;
+                    desktop.write_yaml(writer, indent)?;
+                }
+            }
+            "license" => {
+                if let Some(ref license) = self.license {
+                    writeln!(writer, "{}license:", indent_str)?;
+                    for lc in license {
+                        lc.write_yaml(writer, indent)?;
+                    }
+                }
+            }
+            "src_url" => write_list(writer, &self.src_url)?,
+            "x_exec" => {
+                writeln!(writer, "{}x_exec:", indent_str)?;
+                self.x_exec.write_yaml(writer, indent + 2)?;
+            }
+            _ => {
+                let values = match field {
+                    "build_util" => &self.build_util,
+                    "homepage" => &self.homepage,
+                    "maintainer" => &self.maintainer,
+                    "note" => &self.note,
+                    "provides" => &self.provides,
+                    "repology" => &self.repology,
+                    "tag" => &self.tag,
+                    _ => &None,
+                };
+                if let Some(values) = values {
+                    write_list(writer, values)?;
+                }
             }
         }
 
-        write_field_comments(writer, "x_exec")?;
-        writeln!(writer, "{}x_exec:", indent_str)?;
-        self.x_exec.write_yaml(writer, indent + 2)?;
-
         Ok(())
     }
 }
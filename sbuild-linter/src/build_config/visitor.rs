@@ -1,4 +1,9 @@
-use std::{collections::HashSet, hash::Hash};
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
 
 use colored::Colorize;
 use indexmap::IndexMap;
@@ -9,11 +14,21 @@ use serde::{
 use serde_yml::Value;
 
 use crate::{
+    anchors::Anchors,
+    blame::Blame,
     distro_pkg::DistroPkg,
-    error::{highlight_error_line, ErrorDetails, Severity},
-    get_line_number_for_key,
+    error::{highlight_error_line, render_error, ErrorDetails, Severity},
+    get_line_number_for_json_key, get_line_number_for_key, index_top_level_keys,
     logger::TaskLogger,
-    validator::{is_valid_alpha, is_valid_category, is_valid_url, FIELD_VALIDATORS},
+    report::Report,
+    shapes::FieldShapes,
+    validator::{
+        canonical_category, category_parent, clean_url, distro_naming, find_non_printable,
+        find_placeholder, git_pin, is_git_url, is_incoherent_category, is_valid_alpha,
+        is_valid_url, lowercase_host, normalize_url, path_unsafe_reason,
+        required_fields_for_pkg_type, same_site, url_host, DistroNaming, FIELD_VALIDATORS,
+    },
+    ArchSources, InputFormat, LintOptions, DEFAULT_GIT_PIN_KEYS, UNSUPPORTED_ARCH, VALID_ARCH,
     VALID_PKG_TYPES,
 };
 
@@ -21,8 +36,18 @@ use super::BuildConfig;
 
 pub struct BuildConfigVisitor {
     pub sbuild_str: String,
+    pub input_format: InputFormat,
+    pub options: LintOptions,
+    pub file_path: String,
+    pub report: Option<Arc<Report>>,
+    // shapes of the fields across the batch, recorded if set
+    pub field_shapes: Option<Arc<FieldShapes>>,
     pub visited: HashSet<String>,
     pub errors: Vec<ErrorDetails>,
+    // loaded on the first diagnostic when `vcs_blame` is set
+    pub blame: OnceCell<Option<Blame>>,
+    // line of every top-level YAML key, indexed on the first lookup
+    pub key_lines: OnceCell<HashMap<String, Vec<usize>>>,
     pub logger: TaskLogger,
 }
 
@@ -39,14 +64,11 @@ impl BuildConfigVisitor {
             }
             DistroPkg::InnerNode(map) => {
                 for (key, value) in map {
-                    let new_path = if field_path.is_empty() {
-                        key.clone()
-                    } else {
-                        format!("distro_pkg.{}.{}", field_path, key)
-                    };
+                    let new_path = format!("{}.{}", field_path, key);
 
                     if !self.visited.insert(new_path.clone()) {
                         self.record_error(
+                            "duplicate-field",
                             new_path.clone(),
                             format!("'{}' field is duplicated", new_path),
                             line_number,
@@ -56,6 +78,26 @@ impl BuildConfigVisitor {
                     }
 
                     match value {
+                        DistroPkg::List(list) if list.is_empty() => {
+                            let message = if VALID_ARCH.contains(&key.as_str()) {
+                                format!(
+                                    "'{}' is empty. Remove the '{}' entry if the architecture isn't supported",
+                                    new_path, key
+                                )
+                            } else {
+                                format!(
+                                    "'{}' is empty. Add the package names or check '{}' for typos",
+                                    new_path, key
+                                )
+                            };
+                            self.record_error(
+                                "empty-distro-pkg",
+                                new_path.clone(),
+                                message,
+                                line_number,
+                                Severity::Warn,
+                            );
+                        }
                         DistroPkg::List(list) => {
                             self.check_duplicate_values(list, &new_path, line_number);
                         }
@@ -72,6 +114,47 @@ impl BuildConfigVisitor {
         }
     }
 
+    fn check_free_text(&mut self, field: &str, value: &Value, line_number: usize) {
+        match value {
+            Value::String(s) => {
+                if let Some((offset, c)) = find_non_printable(s) {
+                    self.record_error(
+                        "non-printable",
+                        field.to_string(),
+                        format!("Non-printable character {:?} found at byte {}", c, offset),
+                        line_number,
+                        Severity::Warn,
+                    );
+                }
+                if let Some(placeholder) = find_placeholder(s, &self.options.placeholders) {
+                    self.record_error(
+                        "placeholder-text",
+                        field.to_string(),
+                        format!(
+                            "'{}' looks like a placeholder left from a template",
+                            placeholder
+                        ),
+                        line_number,
+                        Severity::Error,
+                    );
+                }
+            }
+            Value::Sequence(seq) => {
+                for v in seq {
+                    self.check_free_text(field, v, line_number);
+                }
+            }
+            Value::Mapping(map) => {
+                for (k, v) in map {
+                    if let Some(k) = k.as_str() {
+                        self.check_free_text(&format!("{}.{}", field, k), v, line_number);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn check_duplicate_values<T: Eq + Hash + Clone + std::fmt::Display>(
         &mut self,
         list: &[T],
@@ -82,6 +165,7 @@ impl BuildConfigVisitor {
         for item in list {
             if !seen.insert(item.clone()) {
                 self.record_error(
+                    "duplicate-value",
                     field.to_string(),
                     format!("Duplicate value '{}' found in {}", item, field),
                     line_number,
@@ -92,15 +176,555 @@ impl BuildConfigVisitor {
     }
 }
 
+// Lowercased alphanumeric characters, so that `My-App.` and `myapp` compare
+// equal.
+fn significant_chars(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Whether any non-empty leaf list can be used on a supported architecture,
+// i.e. it is either nested under a supported architecture key or not tied to
+// an architecture at all. `supported` is set once an architecture key is
+// found on the path to the leaf.
+fn has_buildable_leaf(distro_pkg: &DistroPkg, supported: Option<bool>) -> bool {
+    match distro_pkg {
+        DistroPkg::List(list) => !list.is_empty() && supported != Some(false),
+        DistroPkg::InnerNode(map) => map.iter().any(|(key, value)| {
+            let supported = if VALID_ARCH.contains(&key.as_str()) {
+                Some(true)
+            } else if UNSUPPORTED_ARCH.contains(&key.as_str()) {
+                Some(false)
+            } else {
+                supported
+            };
+            has_buildable_leaf(value, supported)
+        }),
+    }
+}
+
+// Collects the supported architectures with packages in `distro_pkg`, at any
+// depth, in the order they first appear.
+fn distro_pkg_archs(value: &Value, archs: &mut Vec<String>) {
+    let Some(map) = value.as_mapping() else {
+        return;
+    };
+    for (key, value) in map {
+        let has_packages = match value {
+            Value::Null => false,
+            Value::Sequence(list) => !list.is_empty(),
+            _ => true,
+        };
+        if let Some(key) = key.as_str() {
+            if has_packages && VALID_ARCH.contains(&key) && !archs.iter().any(|arch| arch == key) {
+                archs.push(key.to_string());
+            }
+        }
+        distro_pkg_archs(value, archs);
+    }
+}
+
 impl BuildConfigVisitor {
+    // Checks the package names under a known distro key, at any depth, against
+    // the naming rules of that distro. `naming` is set once such a key is
+    // found on the path to the names.
+    fn check_distro_pkg_names(
+        &mut self,
+        distro_pkg: &DistroPkg,
+        field_path: &str,
+        naming: Option<&'static DistroNaming>,
+        line_number: usize,
+    ) {
+        match distro_pkg {
+            DistroPkg::List(list) => {
+                let Some(naming) = naming else {
+                    return;
+                };
+                for name in list.iter().filter(|name| !naming.is_valid(name)) {
+                    self.record_error(
+                        "invalid-distro-pkg-name",
+                        field_path.to_string(),
+                        format!(
+                            "'{}' isn't a valid {} package name. Names are made of {}",
+                            name, naming.distros[0], naming.summary
+                        ),
+                        line_number,
+                        Severity::Warn,
+                    );
+                }
+            }
+            DistroPkg::InnerNode(map) => {
+                for (key, value) in map {
+                    let naming = distro_naming(&key.to_lowercase()).or(naming);
+                    let field_path = format!("{}.{}", field_path, key);
+                    self.check_distro_pkg_names(value, &field_path, naming, line_number);
+                }
+            }
+        }
+    }
+
+    fn check_description_restates_name(&mut self, values: &IndexMap<String, Value>) {
+        let descriptions: Vec<String> = match values.get("description") {
+            Some(Value::String(description)) => vec![description.clone()],
+            Some(Value::Mapping(map)) => map
+                .values()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            _ => return,
+        };
+
+        for name_field in ["pkg", "pkg_id"] {
+            let Some(name) = values.get(name_field).and_then(Value::as_str) else {
+                continue;
+            };
+            let name_chars = significant_chars(name);
+            if let Some(description) = descriptions
+                .iter()
+                .find(|description| significant_chars(description) == name_chars)
+            {
+                let line_number = self.line_number_for_key("description");
+                self.record_error(
+                    "description-restates-name",
+                    "description".to_string(),
+                    format!(
+                        "'{}' only restates '{}'. Describe what the package does",
+                        description.trim(),
+                        name_field
+                    ),
+                    line_number,
+                    Severity::Warn,
+                );
+                return;
+            }
+        }
+    }
+
+    // Descriptions are shown as one-line summaries, so a trailing newline, as
+    // left by a `|` block, is fine but more lines aren't.
+    fn check_single_line_description(&mut self, value: &Value, line_number: usize) {
+        let multi_line = match value {
+            Value::String(description) => description.trim_end().contains('\n'),
+            Value::Mapping(map) => map
+                .values()
+                .filter_map(Value::as_str)
+                .any(|description| description.trim_end().contains('\n')),
+            _ => false,
+        };
+        if !multi_line {
+            return;
+        }
+        let severity = if self.options.strict_description {
+            Severity::Error
+        } else {
+            Severity::Warn
+        };
+        self.record_error(
+            "multi-line-description",
+            "description".to_string(),
+            "The description spans several lines. Keep it to a one-line summary and move details to 'note'".to_string(),
+            line_number,
+            severity,
+        );
+    }
+
+    // Warns about every line longer than `max_line_length` characters, unless
+    // it contains one of the exempted patterns, by default URLs, which can't
+    // be wrapped.
+    fn check_line_length(&mut self) {
+        let Some(max_line_length) = self.options.max_line_length else {
+            return;
+        };
+        let exempt = &self.options.line_length_exempt;
+        let long_lines: Vec<(usize, usize)> = self
+            .sbuild_str
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .filter(|(_, line)| {
+                !exempt
+                    .iter()
+                    .any(|pattern| !pattern.is_empty() && line.contains(pattern.as_str()))
+            })
+            .map(|(line_number, line)| (line_number, line.chars().count()))
+            .filter(|(_, length)| *length > max_line_length)
+            .collect();
+
+        for (line_number, length) in long_lines {
+            self.record_error(
+                "line-too-long",
+                "document".to_string(),
+                format!(
+                    "Line is {} characters long, which exceeds the limit of {}",
+                    length, max_line_length
+                ),
+                line_number,
+                Severity::Warn,
+            );
+        }
+    }
+
+    // Warns about more categories than `max_categories`, and about general
+    // categories listed next to a more specific one belonging to them, e.g.
+    // `Game` next to `ActionGame`.
+    fn check_category_count(&mut self, values: &IndexMap<String, Value>) {
+        let Some(categories) = values.get("category").and_then(Value::as_sequence) else {
+            return;
+        };
+        let categories: Vec<String> = categories
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        let line_number = self.line_number_for_key("category");
+
+        if categories.len() > self.options.max_categories {
+            self.record_error(
+                "too-many-categories",
+                "category".to_string(),
+                format!(
+                    "'category' has {} entries, more than the limit of {}. Keep the most specific ones",
+                    categories.len(),
+                    self.options.max_categories
+                ),
+                line_number,
+                Severity::Warn,
+            );
+        }
+
+        // each general category is reported once, for the first specific one.
+        // With `primary_categories` the first entry must be a general one, so
+        // it's never redundant.
+        let mut reported = HashSet::new();
+        if let (Some(first), Some(_)) = (categories.first(), &self.options.primary_categories) {
+            reported.extend(canonical_category(first));
+        }
+        for category in &categories {
+            let Some(parent) = category_parent(category) else {
+                continue;
+            };
+            if let Some(redundant) = categories
+                .iter()
+                .find(|other| other.eq_ignore_ascii_case(parent))
+                .filter(|_| reported.insert(parent))
+            {
+                self.record_error(
+                    "redundant-category",
+                    "category".to_string(),
+                    format!(
+                        "'{}' is implied by '{}'. Remove '{}'",
+                        redundant, category, redundant
+                    ),
+                    line_number,
+                    Severity::Warn,
+                );
+            }
+        }
+    }
+
+    fn check_category_pkg_type(&mut self, values: &IndexMap<String, Value>) {
+        let Some(pkg_type) = values.get("pkg_type").and_then(Value::as_str) else {
+            return;
+        };
+        let Some(categories) = values.get("category").and_then(Value::as_sequence) else {
+            return;
+        };
+
+        let pairs = self.options.incoherent_categories.as_deref();
+        let incoherent: Vec<String> = categories
+            .iter()
+            .filter_map(Value::as_str)
+            .filter(|category| is_incoherent_category(pkg_type, category, pairs))
+            .map(str::to_string)
+            .collect();
+        for category in incoherent {
+            let line_number = self.line_number_for_key("category");
+            self.record_error(
+                "incoherent-category",
+                "category".to_string(),
+                format!(
+                    "'{}' doesn't fit pkg_type '{}'. Check the category or the pkg_type",
+                    category, pkg_type
+                ),
+                line_number,
+                Severity::Warn,
+            );
+        }
+    }
+
+    fn check_src_url_is_homepage(&mut self, values: &IndexMap<String, Value>) {
+        let urls = |field: &str| -> Vec<String> {
+            values
+                .get(field)
+                .and_then(Value::as_sequence)
+                .map(|urls| {
+                    urls.iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let homepages: HashSet<String> = urls("homepage")
+            .iter()
+            .map(|url| normalize_url(url).trim_end_matches('/').to_string())
+            .collect();
+
+        for src_url in urls("src_url") {
+            if homepages.contains(normalize_url(&src_url).trim_end_matches('/')) {
+                let line_number = self.line_number_for_key("src_url");
+                self.record_error(
+                    "src-url-is-homepage",
+                    "src_url".to_string(),
+                    format!(
+                        "'{}' is also the homepage. Point 'src_url' at a downloadable source",
+                        src_url
+                    ),
+                    line_number,
+                    Severity::Warn,
+                );
+            }
+        }
+    }
+
+    // Warns when the number of sources doesn't fit the architectures
+    // `distro_pkg` has packages for, e.g. three architectures but two sources,
+    // which usually means a multi-arch config is incomplete.
+    fn check_arch_sources(&mut self, values: &IndexMap<String, Value>) {
+        let Some(rule) = self.options.arch_sources else {
+            return;
+        };
+        let mut archs = Vec::new();
+        if let Some(distro_pkg) = values.get("distro_pkg") {
+            distro_pkg_archs(distro_pkg, &mut archs);
+        }
+        let src_urls = values
+            .get("src_url")
+            .and_then(Value::as_sequence)
+            .map_or(0, |urls| urls.len());
+        if archs.is_empty() || src_urls == 0 || rule.fits(src_urls, archs.len()) {
+            return;
+        }
+
+        let expected = match rule {
+            ArchSources::SharedOrPerArch => format!("1 or {}", archs.len()),
+            ArchSources::PerArch => archs.len().to_string(),
+        };
+        let line_number = self.line_number_for_key("src_url");
+        self.record_error(
+            "arch-sources-mismatch",
+            "src_url".to_string(),
+            format!(
+                "'src_url' has {} entries, but 'distro_pkg' has packages for {} architectures ({}), which need {}",
+                src_urls,
+                archs.len(),
+                archs.join(", "),
+                expected
+            ),
+            line_number,
+            Severity::Warn,
+        );
+    }
+
+    // Warns when the icon is downloaded from a site unrelated to any of the
+    // homepages, unless that site is one of the allowed hosts, e.g. a CDN.
+    fn check_icon_host(&mut self, values: &IndexMap<String, Value>) {
+        let Some(ref allowed_hosts) = self.options.icon_hosts else {
+            return;
+        };
+        let Some(icon_url) = values
+            .get("icon")
+            .and_then(|icon| icon.get("url"))
+            .and_then(Value::as_str)
+        else {
+            return;
+        };
+        let Some(icon_host) = url_host(icon_url) else {
+            return;
+        };
+        let homepage_hosts: Vec<String> = values
+            .get("homepage")
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter_map(url_host)
+            .collect();
+        if homepage_hosts.is_empty()
+            || homepage_hosts
+                .iter()
+                .chain(allowed_hosts)
+                .any(|host| same_site(&icon_host, host))
+        {
+            return;
+        }
+
+        let line_number = self.line_number_for_key("icon");
+        self.record_error(
+            "icon-host-mismatch",
+            "icon.url".to_string(),
+            format!(
+                "'{}' is hosted on {}, not on the homepage's site ({})",
+                icon_url,
+                icon_host,
+                homepage_hosts.join(", ")
+            ),
+            line_number,
+            Severity::Warn,
+        );
+    }
+
+    // Warns at the first field placed after one that should follow it. Fields
+    // missing from the order are ignored.
+    fn check_field_order(&mut self, keys: &[String]) {
+        let Some(ref order) = self.options.field_order else {
+            return;
+        };
+        let position = |key: &str| order.iter().position(|field| field == key);
+
+        let mut last: Option<(&str, usize)> = None;
+        for key in keys {
+            let Some(index) = position(key) else {
+                continue;
+            };
+            match last {
+                Some((previous, previous_index)) if index < previous_index => {
+                    let line_number = self.line_number_for_key(key);
+                    self.record_error(
+                        "field-order",
+                        key.clone(),
+                        format!(
+                            "'{}' should come before '{}'. Expected order: {}",
+                            key,
+                            previous,
+                            order.join(", ")
+                        ),
+                        line_number,
+                        Severity::Warn,
+                    );
+                    return;
+                }
+                _ => last = Some((key, index)),
+            }
+        }
+    }
+
+    fn check_src_url(&mut self, url: &str, line_number: usize) {
+        let default_keys: Vec<String> = DEFAULT_GIT_PIN_KEYS.split(',').map(String::from).collect();
+        let pin_keys = self.options.git_pin_keys.as_ref().unwrap_or(&default_keys);
+        let is_git = is_git_url(url);
+
+        // the fragment of a pinned git URL is the pin, so it is kept
+        let (checked, pin_fragment) = match url.split_once('#') {
+            Some((checked, fragment)) if is_git && git_pin(url, pin_keys) == Some(fragment) => {
+                (checked, Some(fragment))
+            }
+            _ => (url, None),
+        };
+        if let Some(clean) = clean_url(checked) {
+            let clean = match pin_fragment {
+                Some(fragment) => format!("{}#{}", clean, fragment),
+                None => clean,
+            };
+            self.record_error(
+                "unclean-url",
+                "src_url".to_string(),
+                format!(
+                    "'{}' has a fragment or tracking parameters. Use '{}'",
+                    url, clean
+                ),
+                line_number,
+                Severity::Warn,
+            );
+        }
+
+        if let Some(ref keys) = self.options.git_pin_keys {
+            if is_git && git_pin(url, keys).is_none() {
+                let pins: Vec<String> = keys.iter().map(|key| format!("#{}=", key)).collect();
+                self.record_error(
+                    "unpinned-git-source",
+                    "src_url".to_string(),
+                    format!(
+                        "'{}' doesn't pin a revision. Add one of: {}",
+                        url,
+                        pins.join(", ")
+                    ),
+                    line_number,
+                    Severity::Warn,
+                );
+            }
+        }
+    }
+
+    // Describes who last changed the line, or None if that's unknown.
+    fn blame_line(&self, line_number: usize) -> Option<String> {
+        if !self.options.vcs_blame || line_number == 0 {
+            return None;
+        }
+        let blame = self
+            .blame
+            .get_or_init(|| Blame::for_file(&self.file_path))
+            .as_ref()?;
+
+        // the `#!/SBUILD` line is stripped from YAML sources
+        let file_line = match self.input_format {
+            InputFormat::Json => line_number,
+            _ => line_number + 1,
+        };
+        let line = blame.line(file_line)?;
+        if !line.is_committed() {
+            return Some("not committed yet".to_string());
+        }
+        Some(format!(
+            "last changed by {} in {}",
+            line.author,
+            &line.commit[..7]
+        ))
+    }
+
+    // Long URLs wrapped across lines in a plain or quoted YAML scalar are
+    // folded into a single line with spaces in place of the line breaks,
+    // which deserves a more helpful message than an invalid URL.
+    pub fn record_invalid_url(
+        &mut self,
+        field: String,
+        url: &str,
+        message: String,
+        line_number: usize,
+    ) {
+        if url.trim().contains(char::is_whitespace) {
+            self.record_error(
+                "folded-url",
+                field,
+                format!(
+                    "'{}' contains whitespace, most likely from wrapping it across lines. Keep the URL on a single line",
+                    url
+                ),
+                line_number,
+                Severity::Error,
+            );
+        } else {
+            self.record_error("invalid-url", field, message, line_number, Severity::Error);
+        }
+    }
+
     pub fn record_error(
         &mut self,
+        rule: &'static str,
         field: String,
         message: String,
         line_number: usize,
         severity: Severity,
     ) {
+        let message = match self.blame_line(line_number) {
+            Some(blame) => format!("{} ({})", message, blame),
+            None => message,
+        };
         self.errors.push(ErrorDetails {
+            rule,
             field,
             message,
             line_number,
@@ -111,137 +735,643 @@ impl BuildConfigVisitor {
     fn log_error(&self, error: &ErrorDetails) {
         let is_fatal = matches!(error.severity, Severity::Error);
         if is_fatal {
-            self.logger.error(&format!(
-                "{} -> {}",
-                error.field.bold(),
-                error.message.red()
-            ));
+            self.logger.error(render_error(error, self.options.color));
         } else {
-            self.logger.warn(&format!(
-                "{} -> {}",
-                error.field.bold(),
-                error.message.yellow()
-            ));
+            self.logger.warn(render_error(error, self.options.color));
         }
         if error.line_number != 0 {
-            highlight_error_line(&self.sbuild_str, error.line_number, is_fatal, &self.logger);
+            highlight_error_line(
+                &self.sbuild_str,
+                error.line_number,
+                is_fatal,
+                self.options.color,
+                &self.logger,
+            );
         }
     }
-}
 
-impl<'de> Visitor<'de> for BuildConfigVisitor {
-    type Value = BuildConfig;
+    fn line_number_for_key(&self, key: &str) -> usize {
+        self.line_number_for_occurrence(key, 0)
+    }
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        formatter.write_str("a sbuild config")
+    // Line of the given repetition of a top-level key, starting at 0, so a
+    // duplicated key is reported where it is repeated.
+    fn line_number_for_occurrence(&self, key: &str, occurrence: usize) -> usize {
+        match self.input_format {
+            InputFormat::Json => get_line_number_for_json_key(&self.sbuild_str, key),
+            // keys that aren't at the top level, e.g. misplaced ones, are
+            // still searched for anywhere
+            _ => self
+                .key_lines
+                .get_or_init(|| index_top_level_keys(&self.sbuild_str))
+                .get(key)
+                .and_then(|lines| lines.get(occurrence).or(lines.last()))
+                .copied()
+                .unwrap_or_else(|| get_line_number_for_key(&self.sbuild_str, key)),
+        }
     }
 
-    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
-    where
-        A: serde::de::MapAccess<'de>,
-    {
-        let mut values = IndexMap::new();
+    // Returns the scalar written after `key:` on the given line as it appears
+    // in the source. JSON has a single spelling for every scalar, so there is
+    // nothing to look at there.
+    pub fn raw_scalar(&self, line_number: usize) -> Option<&str> {
+        if self.input_format == InputFormat::Json || line_number == 0 {
+            return None;
+        }
+        let line = self.sbuild_str.lines().nth(line_number - 1)?;
+        let (_, value) = line.split_once(':')?;
+        let value = value.split(" #").next().unwrap_or(value).trim();
+        (!value.is_empty()).then_some(value)
+    }
 
-        while let Some((key, value)) = map.next_entry::<String, Value>()? {
-            let line_number = get_line_number_for_key(&self.sbuild_str, &key);
+    pub fn has_fatal_errors(&self) -> bool {
+        self.errors
+            .iter()
+            .any(|e| matches!(e.severity, Severity::Error))
+    }
+
+    // logs all the recorded errors and returns true if any of them is fatal
+    pub fn report_errors(&self) -> bool {
+        if let Some(report) = &self.report {
+            // the `#!/SBUILD` line is stripped from YAML sources before
+            // parsing, so shift the line numbers back to the ones in the file
+            let offset = usize::from(self.input_format != InputFormat::Json);
+            report.record(
+                &self.file_path,
+                self.errors.iter().cloned().map(|mut error| {
+                    if error.line_number != 0 {
+                        error.line_number += offset;
+                    }
+                    error
+                }),
+            );
+        }
+
+        let fatal_errors = self
+            .errors
+            .iter()
+            .filter(|e| matches!(e.severity, Severity::Error))
+            .collect::<Vec<&ErrorDetails>>();
+        if !fatal_errors.is_empty() {
+            for error in &self.errors {
+                self.log_error(error);
+            }
+            self.logger.custom_error(format!(
+                "{}{} found during deserialization.",
+                format!("{} error(s)", self.errors.len()).red(),
+                if self.errors.len() > fatal_errors.len() {
+                    format!(" & {} warning(s)", self.errors.len() - fatal_errors.len()).yellow()
+                } else {
+                    "".yellow()
+                }
+            ));
+            return true;
+        } else if !self.errors.is_empty() {
+            for error in &self.errors {
+                self.log_error(error);
+            }
+            self.logger.custom_error(format!(
+                "{} found during deserialization",
+                format!("{} warning(s)", self.errors.len()).yellow()
+            ))
+        }
+        false
+    }
+
+    fn reject_document<E: de::Error>(mut self, found: &str) -> E {
+        let line_number = self
+            .sbuild_str
+            .lines()
+            .position(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map_or(0, |index| index + 1);
+
+        self.record_error(
+            "not-a-mapping",
+            "document".to_string(),
+            format!(
+                "Expected a mapping of fields at the top level, found {}",
+                found
+            ),
+            line_number,
+            Severity::Error,
+        );
+        self.report_errors();
+        de::Error::custom("")
+    }
+
+    // An SBUILD is a single document, while serde_yml either rejects further
+    // ones without saying where they start or ignores what follows an
+    // explicit end marker. Both usually come from concatenated files.
+    pub fn validate_documents(&mut self) {
+        let mut has_content = false;
+        let mut ended = false;
+        let mut unexpected = None;
+        for (index, line) in self.sbuild_str.lines().enumerate() {
+            let is_marker = |marker: &str| {
+                line.strip_prefix(marker)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if is_marker("---") {
+                if has_content || ended {
+                    unexpected = Some((index + 1, "a second YAML document starts here"));
+                    break;
+                }
+            } else if is_marker("...") {
+                ended = true;
+            } else if ended {
+                unexpected = Some((index + 1, "content follows the end of the document"));
+                break;
+            } else if !line.starts_with('%') {
+                has_content = true;
+            }
+        }
+
+        if let Some((line_number, reason)) = unexpected {
+            self.record_error(
+                "multiple-documents",
+                "document".to_string(),
+                format!(
+                    "File must contain a single SBUILD, but {}. Split concatenated files",
+                    reason
+                ),
+                line_number,
+                Severity::Error,
+            );
+        }
+    }
 
-            if self.visited.contains(&key) {
+    // serde_yml may keep only the last value of a repeated key, and the
+    // visitor doesn't track unknown fields, so repeated top-level keys are
+    // found in the source instead. Every repetition is reported at its line.
+    pub fn validate_duplicate_keys(&mut self) {
+        let mut duplicates = self
+            .key_lines
+            .get_or_init(|| index_top_level_keys(&self.sbuild_str))
+            .iter()
+            .filter(|(key, _)| *key != "<<")
+            .flat_map(|(key, lines)| lines.iter().skip(1).map(move |line| (*line, key.clone())))
+            .collect::<Vec<(usize, String)>>();
+        duplicates.sort();
+
+        for (line_number, key) in duplicates {
+            self.record_error(
+                "duplicate-field",
+                key.clone(),
+                format!("'{}' field is duplicated", key),
+                line_number,
+                Severity::Error,
+            );
+        }
+    }
+
+    pub fn validate_anchors(&mut self) {
+        let anchors = Anchors::parse(&self.sbuild_str);
+
+        for (name, line_number) in anchors.undefined() {
+            self.record_error(
+                "undefined-alias",
+                format!("*{}", name),
+                format!("Alias '*{}' refers to an undefined anchor", name),
+                *line_number,
+                Severity::Error,
+            );
+        }
+
+        for (name, line_number) in anchors.unused() {
+            self.record_error(
+                "unused-anchor",
+                format!("&{}", name),
+                format!("Anchor '&{}' is defined but never used", name),
+                *line_number,
+                Severity::Warn,
+            );
+        }
+    }
+
+    fn visit_merge(
+        &mut self,
+        value: Value,
+        line_number: usize,
+        values: &mut IndexMap<String, Value>,
+    ) {
+        let mappings = match value {
+            Value::Mapping(map) => vec![map],
+            Value::Sequence(seq) if seq.iter().all(Value::is_mapping) => seq
+                .into_iter()
+                .filter_map(|v| match v {
+                    Value::Mapping(map) => Some(map),
+                    _ => None,
+                })
+                .collect(),
+            _ => {
                 self.record_error(
+                    "invalid-type",
+                    "<<".to_string(),
+                    "Merge key must reference a mapping or a sequence of mappings".to_string(),
+                    line_number,
+                    Severity::Error,
+                );
+                return;
+            }
+        };
+
+        for map in mappings {
+            for (key, value) in map {
+                let Some(key) = key.as_str().map(String::from) else {
+                    continue;
+                };
+                if self.visited.contains(&key) {
+                    continue;
+                }
+
+                if FIELD_VALIDATORS.iter().any(|v| v.required && v.name == key) {
+                    self.record_error(
+                        "merge-only-field",
+                        key.clone(),
+                        format!(
+                            "Required field '{}' is only provided through the merge key. Consider defining it explicitly.",
+                            key
+                        ),
+                        line_number,
+                        Severity::Warn,
+                    );
+                }
+                self.visit_field(key, value, line_number, values);
+            }
+        }
+    }
+
+    fn visit_field(
+        &mut self,
+        key: String,
+        value: Value,
+        line_number: usize,
+        values: &mut IndexMap<String, Value>,
+    ) {
+        if self.visited.contains(&key) {
+            // YAML sources are scanned for duplicates before parsing
+            if self.input_format == InputFormat::Json {
+                self.record_error(
+                    "duplicate-field",
                     key.clone(),
                     format!("'{}' field is duplicated", key),
                     line_number,
                     Severity::Error,
                 );
-                continue;
             }
+            return;
+        }
 
-            if let Some(validator) = FIELD_VALIDATORS.iter().find(|v| v.name == key) {
-                if let Some(validated_value) =
-                    validator.validate(&value, &mut self, line_number, validator.required)
-                {
-                    match key.as_ref() {
-                        "distro_pkg" => {
-                            if let Ok(distro_pkg) = DistroPkg::deserialize(validated_value.clone())
-                            {
-                                self.validate_distro_pkg_duplicates(&distro_pkg, "", line_number);
+        if let Some(validator) = FIELD_VALIDATORS.iter().find(|v| v.name == key) {
+            if let Some(mut validated_value) =
+                validator.validate(&value, self, line_number, validator.required)
+            {
+                match key.as_ref() {
+                    "distro_pkg" => {
+                        if let Ok(distro_pkg) = DistroPkg::deserialize(validated_value.clone()) {
+                            self.validate_distro_pkg_duplicates(
+                                &distro_pkg,
+                                "distro_pkg",
+                                line_number,
+                            );
+                            self.check_distro_pkg_names(
+                                &distro_pkg,
+                                "distro_pkg",
+                                None,
+                                line_number,
+                            );
+                            if !has_buildable_leaf(&distro_pkg, None) {
+                                self.record_error(
+                                    "no-supported-arch",
+                                    key.clone(),
+                                    format!(
+                                        "No supported architecture has packages in '{}'. Supported architectures are: {}",
+                                        key,
+                                        VALID_ARCH.join(", ")
+                                    ),
+                                    line_number,
+                                    Severity::Error,
+                                );
                             }
                         }
-                        "pkg" | "pkg_id" | "app_id" => {
-                            if let Some(value) = validated_value.as_str() {
-                                if !is_valid_alpha(value) {
-                                    self.record_error(key.clone(), format!("Invalid '{}': '{}'. Value should only contain alphanumeric, +, -, _, .", key, value), line_number, Severity::Error);
-                                }
+                    }
+                    "pkg" | "pkg_id" | "app_id" => {
+                        if let Some(value) = validated_value.as_str() {
+                            if let Some(reason) = path_unsafe_reason(value) {
+                                self.record_error(
+                                    "path-unsafe-identifier",
+                                    key.clone(),
+                                    format!(
+                                        "Invalid '{}': '{}' {}, so it can't be used as a file name.",
+                                        key, value, reason
+                                    ),
+                                    line_number,
+                                    Severity::Error,
+                                );
+                            } else if !is_valid_alpha(value) {
+                                self.record_error("invalid-identifier", key.clone(), format!("Invalid '{}': '{}'. Value should only contain alphanumeric, +, -, _, .", key, value), line_number, Severity::Error);
                             }
                         }
-                        "category" => {
-                            if let Some(value) = validated_value.as_sequence() {
-                                for v in value {
-                                    let val = v.as_str().unwrap();
-                                    if !is_valid_category(val) {
+                    }
+                    "category" => {
+                        if let Some(value) = validated_value.as_sequence() {
+                            let mut fixed = Vec::with_capacity(value.len());
+                            for (index, v) in value.iter().enumerate() {
+                                // blank and non-string entries are rejected
+                                // by the validator
+                                let Some(val) = v.as_str() else {
+                                    fixed.push(v.clone());
+                                    continue;
+                                };
+                                let canonical = canonical_category(val);
+                                if let Some(canonical) = canonical.filter(|c| *c != val) {
+                                    self.record_error(
+                                        "non-canonical-category",
+                                        key.clone(),
+                                        format!("'{}' should be spelled '{}'", val, canonical),
+                                        line_number,
+                                        Severity::Warn,
+                                    );
+                                }
+                                let val = match canonical {
+                                    Some(canonical) if self.options.fix => canonical,
+                                    _ => val,
+                                };
+                                fixed.push(Value::String(val.to_string()));
+
+                                if let (0, Some(primary_categories)) =
+                                    (index, &self.options.primary_categories)
+                                {
+                                    let val = canonical.unwrap_or(val);
+                                    if !primary_categories.iter().any(|c| c == val) {
                                         self.record_error(
-                                            key.clone(),
+                                            "invalid-primary-category",
+                                            format!("{}[0]", key),
                                             format!(
-                                                "Invalid '{}': '{}' is not a valid category.",
-                                                key, val
+                                                "'{}' is not a primary category. The first entry of '{}' must be one of: {}",
+                                                val,
+                                                key,
+                                                primary_categories.join(", ")
                                             ),
                                             line_number,
                                             Severity::Error,
                                         );
                                     }
-                                }
-                            }
-                        }
-                        "pkg_type" => {
-                            if let Some(pkg_type) = validated_value.as_str() {
-                                if !VALID_PKG_TYPES.contains(&pkg_type) {
+                                } else if canonical.is_none() {
                                     self.record_error(
+                                        "invalid-category",
                                         key.clone(),
                                         format!(
-                                            "Invalid '{}': '{}'. Valid values are: {:?}",
-                                            key, pkg_type, VALID_PKG_TYPES
+                                            "Invalid '{}': '{}' is not a valid category.",
+                                            key, val
                                         ),
                                         line_number,
                                         Severity::Error,
                                     );
                                 }
                             }
+                            validated_value = Value::Sequence(fixed);
                         }
-                        "homepage" | "src_url" => {
-                            if let Some(value) = validated_value.as_sequence() {
-                                for v in value {
-                                    let val = v.as_str().unwrap();
-                                    if !is_valid_url(val) {
-                                        self.record_error(
-                                            key.clone(),
-                                            format!(
-                                                "Invalid '{}': '{}' is not a valid URL.",
-                                                key, val
-                                            ),
-                                            line_number,
-                                            Severity::Error,
-                                        );
-                                    }
+                    }
+                    "pkg_type" => {
+                        if let Some(pkg_type) = validated_value.as_str() {
+                            let canonical = VALID_PKG_TYPES
+                                .iter()
+                                .find(|valid| valid.eq_ignore_ascii_case(pkg_type))
+                                .filter(|valid| **valid != pkg_type);
+                            if let Some(canonical) = canonical {
+                                self.record_error(
+                                    "non-canonical-pkg-type",
+                                    key.clone(),
+                                    format!("'{}' should be spelled '{}'", pkg_type, canonical),
+                                    line_number,
+                                    Severity::Warn,
+                                );
+                                if self.options.fix {
+                                    validated_value = Value::String(canonical.to_string());
+                                }
+                            } else if !VALID_PKG_TYPES.contains(&pkg_type) {
+                                self.record_error(
+                                    "invalid-pkg-type",
+                                    key.clone(),
+                                    format!(
+                                        "Invalid '{}': '{}'. Valid values are: {:?}",
+                                        key, pkg_type, VALID_PKG_TYPES
+                                    ),
+                                    line_number,
+                                    Severity::Error,
+                                );
+                            }
+                        }
+                    }
+                    "homepage" | "src_url" => {
+                        if let Some(value) = validated_value.as_sequence() {
+                            let mut fixed = Vec::with_capacity(value.len());
+                            for v in value {
+                                let Some(val) = v.as_str() else {
+                                    fixed.push(v.clone());
+                                    continue;
+                                };
+                                if !is_valid_url(val) {
+                                    self.record_invalid_url(
+                                        key.clone(),
+                                        val,
+                                        format!("Invalid '{}': '{}' is not a valid URL.", key, val),
+                                        line_number,
+                                    );
+                                    fixed.push(v.clone());
+                                    continue;
+                                }
+                                let lowercased = lowercase_host(val);
+                                if let Some(ref lowercased) = lowercased {
+                                    self.record_error(
+                                        "uppercase-url-host",
+                                        key.clone(),
+                                        format!(
+                                            "'{}' has uppercase letters in its host. Use '{}'",
+                                            val, lowercased
+                                        ),
+                                        line_number,
+                                        Severity::Warn,
+                                    );
+                                }
+                                let val = match lowercased {
+                                    Some(ref lowercased) if self.options.fix => lowercased,
+                                    _ => val,
+                                };
+                                fixed.push(Value::String(val.to_string()));
+                                // homepages legitimately link to a section of
+                                // a page
+                                if key == "src_url" {
+                                    self.check_src_url(val, line_number);
                                 }
                             }
+                            validated_value = Value::Sequence(fixed);
+                        }
+                    }
+                    "description" | "note" | "maintainer" => {
+                        self.check_free_text(&key, &validated_value, line_number);
+                        if key == "description" {
+                            self.check_single_line_description(&validated_value, line_number);
                         }
-                        _ => {}
                     }
-                    values.insert(key.clone(), validated_value);
+                    _ => {}
                 }
-                self.visited.insert(key);
+                values.insert(key.clone(), validated_value);
+            }
+            self.visited.insert(key);
+        } else {
+            let severity = if self.options.strict_fields {
+                Severity::Error
             } else {
+                Severity::Warn
+            };
+            self.record_error(
+                "unknown-field",
+                key.clone(),
+                format!("'{}' is not a valid field.", key),
+                line_number,
+                severity,
+            );
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for BuildConfigVisitor {
+    type Value = BuildConfig;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sbuild config")
+    }
+
+    fn visit_seq<A>(self, _seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        Err(self.reject_document("a list"))
+    }
+
+    fn visit_str<E>(self, _v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("a scalar"))
+    }
+
+    fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("a scalar"))
+    }
+
+    fn visit_i64<E>(self, _v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("a scalar"))
+    }
+
+    fn visit_u64<E>(self, _v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("a scalar"))
+    }
+
+    fn visit_f64<E>(self, _v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("a scalar"))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("an empty document"))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(self.reject_document("an empty document"))
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut values = IndexMap::new();
+
+        let mut keys = Vec::new();
+        let mut merges = Vec::new();
+        let mut field_count = 0;
+
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            let occurrence = keys.iter().filter(|k| **k == key).count();
+            let line_number = self.line_number_for_occurrence(&key, occurrence);
+            if let Some(ref field_shapes) = self.field_shapes {
+                field_shapes.record(&self.file_path, &key, &value);
+            }
+
+            field_count += 1;
+            if field_count == self.options.max_fields + 1 {
                 self.record_error(
+                    "too-many-fields",
                     key.clone(),
-                    format!("'{}' is not a valid field.", key),
+                    format!(
+                        "Document has more than {} top-level fields",
+                        self.options.max_fields
+                    ),
                     line_number,
                     Severity::Warn,
                 );
             }
+
+            // merge keys are resolved after the explicit fields so that
+            // those always take precedence over the merged ones
+            if key == "<<" {
+                merges.push((value, line_number));
+                continue;
+            }
+
+            keys.push(key.clone());
+            self.visit_field(key, value, line_number, &mut values);
+        }
+
+        for (value, line_number) in merges {
+            self.visit_merge(value, line_number, &mut values);
+        }
+
+        if self.sbuild_str.len() > self.options.max_size {
+            self.record_error(
+                "document-too-large",
+                "document".to_string(),
+                format!(
+                    "Document is {} bytes, which exceeds the limit of {} bytes",
+                    self.sbuild_str.len(),
+                    self.options.max_size
+                ),
+                0,
+                Severity::Warn,
+            );
         }
 
         for validator in FIELD_VALIDATORS {
             if validator.required && !self.visited.contains(validator.name) {
                 self.record_error(
+                    "missing-field",
                     validator.name.to_string(),
                     format!("Missing required field: {}", validator.name),
                     0,
@@ -250,35 +1380,87 @@ impl<'de> Visitor<'de> for BuildConfigVisitor {
             }
         }
 
-        let fatal_errors = self
-            .errors
-            .iter()
-            .filter(|e| matches!(e.severity, Severity::Error))
-            .collect::<Vec<&ErrorDetails>>();
-        if !fatal_errors.is_empty() {
-            for error in &self.errors {
-                self.log_error(error);
-            }
-            self.logger.custom_error(&format!(
-                "{}{} found during deserialization.",
-                format!("{} error(s)", self.errors.len()).red(),
-                if self.errors.len() > fatal_errors.len() {
-                    format!(" & {} warning(s)", self.errors.len() - fatal_errors.len()).yellow()
-                } else {
-                    "".yellow()
+        let pkg_type = values
+            .get("pkg_type")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if let Some(pkg_type) = pkg_type {
+            let gui_types = self.options.gui_types.as_deref();
+            for field in required_fields_for_pkg_type(&pkg_type, gui_types) {
+                if !self.visited.contains(*field) {
+                    let line_number = self.line_number_for_key("pkg_type");
+                    self.record_error(
+                        "missing-pkg-type-field",
+                        field.to_string(),
+                        format!(
+                            "Missing field required by pkg_type '{}': {}",
+                            pkg_type, field
+                        ),
+                        line_number,
+                        Severity::Error,
+                    );
                 }
-            ));
-            return Err(de::Error::custom(""));
-        } else if !self.errors.is_empty() {
-            for error in &self.errors {
-                self.log_error(error);
             }
-            self.logger.custom_error(&format!(
-                "{} found during deserialization",
-                format!("{} warning(s)", self.errors.len()).yellow()
-            ))
+        }
+
+        self.check_category_pkg_type(&values);
+        self.check_category_count(&values);
+        self.check_description_restates_name(&values);
+        self.check_src_url_is_homepage(&values);
+        self.check_icon_host(&values);
+        self.check_arch_sources(&values);
+        self.check_field_order(&keys);
+        self.check_line_length();
+
+        if self.report_errors() {
+            return Err(de::Error::custom(""));
         }
 
         Ok(BuildConfig::from_value_map(&values))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::lint_string;
+
+    const HEADER: &str = "#!/SBUILD
+_disabled: false
+pkg: foo
+description: \"A test package\"
+src_url:
+  - \"https://example.com\"
+x_exec:
+  shell: sh
+  run: echo hi
+";
+
+    fn fields_for(rule: &str, distro_pkg: &str) -> Vec<String> {
+        lint_string(&format!("{}distro_pkg:\n{}", HEADER, distro_pkg))
+            .into_iter()
+            .filter(|e| e.rule == rule)
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn distro_pkg_duplicates_are_reported_with_their_full_path() {
+        let top_level = fields_for("duplicate-value", "  alpine:\n    - foo\n    - foo\n");
+        assert_eq!(top_level, ["distro_pkg.alpine"]);
+
+        let nested = fields_for(
+            "duplicate-value",
+            "  debian:\n    x86_64:\n      - foo\n      - foo\n",
+        );
+        assert_eq!(nested, ["distro_pkg.debian.x86_64"]);
+    }
+
+    #[test]
+    fn distro_pkg_empty_entries_are_reported_with_their_full_path() {
+        let fields = fields_for(
+            "empty-distro-pkg",
+            "  alpine: []\n  debian:\n    x86_64:\n      - foo\n    aarch64: []\n",
+        );
+        assert_eq!(fields, ["distro_pkg.alpine", "distro_pkg.debian.aarch64"]);
+    }
+}
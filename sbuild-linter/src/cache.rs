@@ -0,0 +1,88 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tempfile::NamedTempFile;
+
+// Results of expensive checks stored as one JSON file per entry, so they can
+// be reused across runs and shared by processes running at the same time.
+pub struct Cache {
+    dir: PathBuf,
+    // mixed into every key, e.g. the version of the tool producing the
+    // results, so entries from other versions are never reused
+    salt: String,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>, salt: impl Into<String>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Cache {
+            dir,
+            salt: salt.into(),
+        })
+    }
+
+    pub fn key(&self, parts: &[&str]) -> String {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        self.salt.hash(&mut hasher);
+        parts.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    // Unreadable or corrupt entries are treated as missing.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let content = fs::read(self.entry_path(key)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    // The entry is written to a temporary file in the cache directory and
+    // renamed into place, which is atomic on the same filesystem. Concurrent
+    // readers see either no entry or a complete one, and concurrent writers
+    // of the same key just replace each other's identical result.
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) -> io::Result<()> {
+        let mut tmp = NamedTempFile::new_in(&self.dir)?;
+        serde_json::to_writer(&mut tmp, value)?;
+        tmp.flush()?;
+        tmp.persist(self.entry_path(key)).map_err(|err| err.error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip_and_corrupt_ones_are_missing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = Cache::new(dir.path(), "salt").unwrap();
+        let key = cache.key(&["script"]);
+
+        assert_eq!(cache.get::<Vec<u32>>(&key), None);
+        cache.put(&key, &vec![1, 2]).unwrap();
+        assert_eq!(cache.get::<Vec<u32>>(&key), Some(vec![1, 2]));
+
+        fs::write(cache.entry_path(&key), "{").unwrap();
+        assert_eq!(cache.get::<Vec<u32>>(&key), None);
+    }
+
+    #[test]
+    fn keys_depend_on_the_salt() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let a = Cache::new(dir.path(), "0.9.0").unwrap();
+        let b = Cache::new(dir.path(), "0.10.0").unwrap();
+        assert_eq!(a.key(&["script"]), a.key(&["script"]));
+        assert_ne!(a.key(&["script"]), b.key(&["script"]));
+    }
+}
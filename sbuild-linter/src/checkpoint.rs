@@ -0,0 +1,67 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+// Outcomes of the files processed by earlier runs over the same batch, so a
+// restarted run can skip them. Each file is recorded on its own line as
+// `passed<TAB>PATH` or `failed<TAB>PATH` as soon as it completes, with a
+// single append, so the checkpoint is usable whenever the run is stopped.
+pub struct Checkpoint {
+    done: HashMap<String, bool>,
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        // a line cut short by an interrupted write has no newline yet, and
+        // is left out so the file is linted again
+        let complete = content.rfind('\n').map_or("", |end| &content[..end + 1]);
+        let done = complete
+            .lines()
+            .filter_map(|line| match line.split_once('\t')? {
+                ("passed", file_path) => Some((file_path.to_string(), true)),
+                ("failed", file_path) => Some((file_path.to_string(), false)),
+                _ => None,
+            })
+            .collect();
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if complete.len() != content.len() {
+            file.write_all(b"\n")?;
+        }
+        Ok(Checkpoint {
+            done,
+            file: Mutex::new(file),
+        })
+    }
+
+    // Whether the file passed in an earlier run, or None if it wasn't done.
+    pub fn outcome(&self, file_path: &str) -> Option<bool> {
+        self.done.get(file_path).copied()
+    }
+
+    pub fn record(&self, file_path: &str, passed: bool) -> io::Result<()> {
+        let line = format!(
+            "{}\t{}\n",
+            if passed { "passed" } else { "failed" },
+            file_path
+        );
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| io::Error::other("checkpoint lock poisoned"))?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()
+    }
+}
@@ -0,0 +1,549 @@
+use std::{path::PathBuf, str::FromStr, thread, time::Duration};
+
+use crate::priority::CpuList;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueHint};
+use clap_complete::Shell;
+use sbuild_linter::{
+    error::FailOn,
+    logger::Verbosity,
+    oplog::OpLevel,
+    report::{GroupBy, OutputFormat},
+    shellcheck, ArchSources, InputFormat, DEFAULT_FIELD_ORDER, DEFAULT_GIT_PIN_KEYS,
+    DEFAULT_ICON_HOSTS, DEFAULT_LINE_LENGTH_EXEMPT, DEFAULT_MAX_CATEGORIES, DEFAULT_MAX_FIELDS,
+    DEFAULT_MAX_SIZE, DEFAULT_PLACEHOLDERS, DEFAULT_PRIMARY_CATEGORIES, DEFAULT_SET_OPTIONS,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "sbuild-linter",
+    version,
+    about = "A linter for SBUILD package files.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    // Linting is the default when no subcommand is given, so the flags are
+    // also accepted at the top level
+    #[command(flatten)]
+    pub lint: LintArgs,
+
+    /// When to color the output: always, auto (on a terminal, unless NO_COLOR is set), never
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorChoice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Always,
+    Auto,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "always" => Ok(ColorChoice::Always),
+            "auto" => Ok(ColorChoice::Auto),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("Invalid color choice: '{}'", s)),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Validate SBUILD package files (default)
+    Lint(Box<LintArgs>),
+    /// Show the explanation of a rule
+    Explain {
+        /// Rule ID, as printed by `list-rules`
+        rule: String,
+    },
+    /// List the rules checked by the linter
+    ListRules,
+    /// List the categories accepted in `category`
+    ListCategories {
+        /// Print the list as a JSON array
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the JSON Schema of the SBUILD format
+    DumpSchema,
+    /// Compare the diagnostics of two `--format json` results
+    Diff {
+        /// Results of the earlier run
+        old: PathBuf,
+        /// Results of the later run
+        new: PathBuf,
+    },
+    /// Print the completion script for a shell
+    Completions {
+        /// Shell to complete in: bash, elvish, fish, powershell, zsh
+        shell: Shell,
+    },
+}
+
+// How the files are distributed over the jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcurrencyModel {
+    // fixed workers fed through a bounded queue
+    Threadpool,
+    // a rayon work-stealing pool
+    Rayon,
+}
+
+impl FromStr for ConcurrencyModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "threadpool" => Ok(ConcurrencyModel::Threadpool),
+            "rayon" => Ok(ConcurrencyModel::Rayon),
+            _ => Err(format!("Invalid concurrency model: '{}'", s)),
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct LintArgs {
+    /// Enable pkgver mode
+    #[arg(short, long)]
+    pub pkgver: bool,
+
+    /// Resolve the pkgver twice and warn if the results differ
+    #[arg(long, requires = "pkgver")]
+    pub pkgver_verify: bool,
+
+    /// Disable shellcheck
+    #[arg(long)]
+    pub no_shellcheck: bool,
+
+    /// Only check the structure and fields of the files, skipping shellcheck, pkgver scripts and URL checks
+    #[arg(long, conflicts_with_all = ["pkgver", "check_urls"])]
+    pub structure_only: bool,
+
+    /// Lowest shellcheck severity that fails a file: style, info, warning, error
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        default_value = "error",
+        conflicts_with_all = ["no_shellcheck", "structure_only"]
+    )]
+    pub shellcheck_severity: shellcheck::Level,
+
+    /// Lint N files at the same time, each on its own worker thread (default N: one per CPU)
+    #[arg(
+        long,
+        visible_alias = "threads",
+        value_name = "N",
+        num_args = 0..=1,
+        value_parser = parse_jobs,
+        overrides_with = "no_parallel"
+    )]
+    pub parallel: Option<Option<usize>>,
+
+    /// Lint one file at a time, the default, overriding an earlier --parallel
+    #[arg(long, overrides_with = "parallel")]
+    pub no_parallel: bool,
+
+    /// How parallel jobs are scheduled: threadpool, rayon (work-stealing)
+    #[arg(long, value_name = "MODEL", default_value = "threadpool")]
+    pub concurrency_model: ConcurrencyModel,
+
+    /// Run at nice level N, e.g. 10 to leave the CPU to other jobs first
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i32).range(-20..=19))]
+    pub nice: Option<i32>,
+
+    /// Only run on CPUS, e.g. 0-3,6 (Linux only)
+    #[arg(long, value_name = "CPUS")]
+    pub cpu_affinity: Option<CpuList>,
+
+    /// Lint the files N times and print timing statistics, for benchmarking
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = parse_jobs)]
+    pub repeat: usize,
+
+    /// Show the output of parallel jobs, one file at a time in input order
+    #[arg(long, requires = "parallel")]
+    pub ordered: bool,
+
+    /// Show the output of parallel jobs, one file at a time as they complete
+    #[arg(long, conflicts_with = "ordered")]
+    pub live_log: bool,
+
+    /// Replace the original file on success
+    #[arg(short, long)]
+    pub inplace: bool,
+
+    /// Write the validated files to DIR, mirroring their relative paths
+    #[arg(long, value_name = "DIR", conflicts_with = "inplace")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Print the validated YAML to stdout instead of writing files
+    #[arg(long, conflicts_with_all = ["inplace", "out_dir", "count"])]
+    pub stdout: bool,
+
+    /// File to store successful packages list
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub success: Option<String>,
+
+    /// File to store failed packages list
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub fail: Option<String>,
+
+    /// Empty the --success and --fail files first instead of appending to them
+    #[arg(long)]
+    pub truncate_stores: bool,
+
+    /// Time after which pkgver scripts and URL checks time out, e.g. 30s, 2m or 1h500ms, bare numbers being seconds, 0 to disable
+    #[arg(long, value_name = "DURATION", default_value = "30", value_parser = parse_duration)]
+    pub timeout: Duration,
+
+    /// Write per-file phase timings as folded stacks to PATH
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub profile: Option<String>,
+
+    /// Write a log of dispatched files, cache lookups and timings to PATH
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Detail of --log-file: info, debug
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        default_value = "info",
+        requires = "log_file"
+    )]
+    pub log_level: OpLevel,
+
+    /// Reuse shellcheck results stored in DIR, which may be shared by several processes
+    #[arg(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Input format: auto, yaml, json
+    #[arg(long, value_name = "FMT", default_value = "auto")]
+    pub input_format: InputFormat,
+
+    /// Check that src_url and homepage URLs are reachable
+    #[arg(long)]
+    pub check_urls: bool,
+
+    /// Warn when a homepage isn't served as text/html
+    #[arg(long, requires = "check_urls")]
+    pub check_homepage_type: bool,
+
+    /// Maximum concurrent URL checks
+    #[arg(long, value_name = "N", default_value_t = 8, value_parser = parse_jobs)]
+    pub url_jobs: usize,
+
+    /// Maximum concurrent URL checks per host
+    #[arg(long, value_name = "N", default_value_t = 2, value_parser = parse_jobs)]
+    pub url_host_jobs: usize,
+
+    /// Delay between requests to the same host
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub url_delay: u64,
+
+    /// Treat unknown fields as errors
+    #[arg(long)]
+    pub strict_fields: bool,
+
+    /// Treat descriptions spanning several lines as errors
+    #[arg(long)]
+    pub strict_description: bool,
+
+    /// Warn when a file has more than N top-level fields
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_FIELDS)]
+    pub max_fields: usize,
+
+    /// Warn when a file has more than N categories
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_CATEGORIES)]
+    pub max_categories: usize,
+
+    /// Warn when a file is larger than BYTES
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_SIZE)]
+    pub max_size: usize,
+
+    /// Require the first category to be one of LIST (default: freedesktop main categories)
+    #[arg(
+        long,
+        value_name = "LIST",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = DEFAULT_PRIMARY_CATEGORIES
+    )]
+    pub primary_categories: Option<Vec<String>>,
+
+    /// Warn when a git src_url doesn't pin a revision with one of the KEYS (default: tag,commit)
+    #[arg(
+        long,
+        value_name = "KEYS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = DEFAULT_GIT_PIN_KEYS
+    )]
+    pub require_git_pin: Option<Vec<String>>,
+
+    /// Warn when the icon isn't hosted on the homepage's site or one of the HOSTS (default: githubusercontent.com)
+    #[arg(
+        long,
+        value_name = "HOSTS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = DEFAULT_ICON_HOSTS
+    )]
+    pub check_icon_host: Option<Vec<String>>,
+
+    /// Warn when the src_url count doesn't fit the distro_pkg architectures: shared-or-per-arch (default), per-arch
+    #[arg(
+        long,
+        value_name = "RULE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "shared-or-per-arch"
+    )]
+    pub check_arch_sources: Option<ArchSources>,
+
+    /// Warn when x_exec.run doesn't start by enabling the shell OPTIONS with set (default: errexit)
+    #[arg(
+        long,
+        value_name = "OPTIONS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = DEFAULT_SET_OPTIONS
+    )]
+    pub require_set: Option<Vec<String>>,
+
+    /// Warn about these pkg_type:Category pairs instead of the built-in ones, empty to disable
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub incoherent_categories: Option<Vec<String>>,
+
+    /// Require app_id for these pkg_type values instead of the desktop image formats, empty for none
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub gui_types: Option<Vec<String>>,
+
+    /// Warn about lines longer than N characters
+    #[arg(long, value_name = "N")]
+    pub max_line_length: Option<usize>,
+
+    /// Lines containing one of PATTERNS may exceed --max-line-length, empty to exempt none
+    #[arg(
+        long,
+        value_name = "PATTERNS",
+        value_delimiter = ',',
+        default_value = DEFAULT_LINE_LENGTH_EXEMPT,
+        requires = "max_line_length"
+    )]
+    pub line_length_exempt: Vec<String>,
+
+    /// Markers of template text rejected in free-text fields, empty to only reject multi-word <...>
+    #[arg(
+        long,
+        value_name = "LIST",
+        value_delimiter = ',',
+        default_value = DEFAULT_PLACEHOLDERS
+    )]
+    pub placeholders: Vec<String>,
+
+    /// Warn when fields aren't in the order of LIST (default: the order of the validated output)
+    #[arg(
+        long,
+        value_name = "LIST",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = DEFAULT_FIELD_ORDER
+    )]
+    pub field_order: Option<Vec<String>>,
+
+    /// Correct script whitespace, the case of categories, pkg_type and URL hosts, and field order in the validated output
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Add who last changed the line to diagnostics, using git blame
+    #[arg(long)]
+    pub vcs_blame: bool,
+
+    /// Allow scripts to use variable NAME without assigning it (can be repeated)
+    #[arg(long, value_name = "NAME", value_delimiter = ',')]
+    pub allow_var: Vec<String>,
+
+    /// Report pkg, pkg_id and app_id values shared by several files
+    #[arg(long)]
+    pub check_unique: bool,
+
+    /// Warn about fields written as a scalar, list or mapping in some files and differently in others
+    #[arg(long)]
+    pub check_field_shapes: bool,
+
+    /// Warn about files whose configs are identical once normalized
+    #[arg(long)]
+    pub check_duplicates: bool,
+
+    /// Descend at most N levels below the given directories (0: only their files)
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Only lint the files directly inside the given directories, same as --max-depth 0
+    #[arg(long, conflicts_with = "max_depth")]
+    pub no_recurse: bool,
+
+    /// Skip files whose relative path matches GLOB (can be repeated)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Check the tools and resources needed by the given flags, then exit
+    #[arg(long)]
+    pub check_env: bool,
+
+    /// Print what the linter parsed from each file passing validation to stderr, for debugging
+    #[arg(long)]
+    pub dump_ast: bool,
+
+    /// Only print the number of passed and failed files
+    #[arg(long)]
+    pub count: bool,
+
+    /// Don't print the summary of passed and failed files at the end
+    #[arg(long, conflicts_with = "count")]
+    pub no_summary: bool,
+
+    /// Only show errors, without warnings, successes and the summary
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Also show the steps taken for each file, and with -vv the operations of the linter
+    #[arg(short, long, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Output format: text, json, ndjson, checkstyle, junit
+    #[arg(
+        long,
+        value_name = "FMT",
+        default_value = "text",
+        conflicts_with = "count"
+    )]
+    pub format: OutputFormat,
+
+    /// Write json and ndjson output as one object per file or per diagnostic: file, diagnostic
+    #[arg(long, value_name = "GROUPING", default_value = "file")]
+    pub group_by: GroupBy,
+
+    /// Lowest severity that fails a file and the run: error, warn, none (always exit 0)
+    #[arg(long, value_name = "SEVERITY", default_value = "error")]
+    pub fail_on: FailOn,
+
+    /// Also write the result of each file to DIR, in the output format
+    #[arg(long, value_name = "DIR")]
+    pub report_dir: Option<PathBuf>,
+
+    /// Write a JSON summary of the run, with the version, arguments and environment, to PATH
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub report: Option<PathBuf>,
+
+    /// Record the outcome of each file in PATH and skip the files it lists as done
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub resume: Option<PathBuf>,
+
+    /// Also lint the paths listed in FILE, one per line, or read from stdin if FILE is -
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub input_list: Option<PathBuf>,
+
+    /// One or more package files, or directories to search for .yaml and .yml files
+    #[arg(
+        value_name = "FILE",
+        value_hint = ValueHint::AnyPath,
+        required_unless_present_any = ["check_env", "input_list"]
+    )]
+    pub files: Vec<String>,
+}
+
+impl LintArgs {
+    // Number of files linted at the same time: one without `--parallel`, and
+    // one per CPU when it is given without a value.
+    pub fn jobs(&self) -> usize {
+        match self.parallel {
+            None => 1,
+            Some(None) => thread::available_parallelism().map_or(1, |cpus| cpus.get()),
+            Some(Some(jobs)) => jobs,
+        }
+    }
+
+    // Errors, warnings and successes are shown by default, `-q` leaves the
+    // errors only, `-v` adds the steps taken and `-vv` the operation log.
+    pub fn verbosity(&self) -> Verbosity {
+        match (self.quiet, self.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+}
+
+// Job counts are used as the number of workers and permits, and repetitions
+// as the number of runs, so 0 would never run anything.
+fn parse_jobs(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(jobs) => Ok(jobs),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+// Durations are written as numbers with a unit of ms, s, m or h, several of
+// which can follow each other like `1h30m`. A bare number is taken as seconds,
+// as that is all the flags used to accept.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| format!("expected a number in '{}'", value))?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let duration = match &rest[..unit] {
+            "ms" => Duration::from_millis(amount),
+            "s" => Duration::from_secs(amount),
+            "m" => Duration::from_secs(amount.saturating_mul(60)),
+            "h" => Duration::from_secs(amount.saturating_mul(3600)),
+            "" => return Err(format!("missing unit after {} in '{}'", amount, value)),
+            unit => return Err(format!("unknown unit '{}', use ms, s, m or h", unit)),
+        };
+        total = total.saturating_add(duration);
+        rest = &rest[unit..];
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_jobs_rejects_zero() {
+        assert_eq!(parse_jobs("0"), Err("must be at least 1".to_string()));
+        assert_eq!(parse_jobs("1"), Ok(1));
+        assert_eq!(parse_jobs("100000"), Ok(100000));
+        assert!(parse_jobs("-1").is_err());
+        assert!(parse_jobs("four").is_err());
+    }
+}
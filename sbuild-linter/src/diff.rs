@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use crate::report::{Diagnostic, Results};
+
+#[derive(Default)]
+pub struct FileDiff {
+    pub path: String,
+    pub new: Vec<Diagnostic>,
+    pub fixed: Vec<Diagnostic>,
+    pub unchanged: Vec<Diagnostic>,
+}
+
+// Compares the diagnostics of two runs file by file. Line numbers are not
+// part of the comparison, since unrelated edits above a diagnostic shift them
+// without changing what is reported.
+pub fn diff_results(old: &Results, new: &Results) -> Vec<FileDiff> {
+    let mut paths: BTreeMap<&str, (Vec<&Diagnostic>, Vec<&Diagnostic>)> = BTreeMap::new();
+    for file in &old.files {
+        paths
+            .entry(&file.path)
+            .or_default()
+            .0
+            .extend(&file.diagnostics);
+    }
+    for file in &new.files {
+        paths
+            .entry(&file.path)
+            .or_default()
+            .1
+            .extend(&file.diagnostics);
+    }
+
+    paths
+        .into_iter()
+        .map(|(path, (old, mut new))| {
+            let mut file_diff = FileDiff {
+                path: path.to_string(),
+                ..Default::default()
+            };
+
+            for diagnostic in old {
+                match new
+                    .iter()
+                    .position(|other| same_diagnostic(diagnostic, other))
+                {
+                    Some(index) => file_diff.unchanged.push(new.remove(index).clone()),
+                    None => file_diff.fixed.push(diagnostic.clone()),
+                }
+            }
+            file_diff.new = new.into_iter().cloned().collect();
+
+            file_diff
+        })
+        .collect()
+}
+
+fn same_diagnostic(a: &Diagnostic, b: &Diagnostic) -> bool {
+    a.rule == b.rule && a.severity == b.severity && a.field == b.field && a.message == b.message
+}
@@ -1,7 +1,4 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter, Write},
-};
+use std::io::{self, Write};
 
 use indexmap::IndexMap;
 
@@ -20,7 +17,7 @@ pub enum DisabledReason {
 }
 
 impl DisabledReason {
-    pub fn write_yaml(&self, writer: &mut BufWriter<File>, indent: usize) -> io::Result<()> {
+    pub fn write_yaml<W: Write>(&self, writer: &mut W, indent: usize) -> io::Result<()> {
         let indent_str = " ".repeat(indent);
 
         match self {
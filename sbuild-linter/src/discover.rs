@@ -0,0 +1,104 @@
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::Path,
+};
+
+// Extensions of the files picked up when a directory is given.
+const SBUILD_EXTENSIONS: [&str; 2] = ["yaml", "yml"];
+
+// Replaces the directories among the given paths by the SBUILD files they
+// contain, descending at most `max_depth` levels of subdirectories, or without
+// limit if None. Entries are sorted by name and hidden ones and links to
+// directories are skipped, so the same tree always produces the same list.
+pub fn expand_paths(paths: Vec<String>, max_depth: Option<usize>) -> Vec<String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if Path::new(&path).is_dir() {
+            walk(Path::new(&path), 0, max_depth, &mut files);
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+fn walk(dir: &Path, depth: usize, max_depth: Option<usize>, files: &mut Vec<String>) {
+    // unreadable directories are skipped, like the ones past the depth limit
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            if max_depth.is_none_or(|max_depth| depth < max_depth) {
+                walk(&path, depth + 1, max_depth, files);
+            }
+        } else if file_type.is_symlink() && path.is_dir() {
+            // links to directories aren't followed, as one pointing back up
+            // the tree would be walked endlessly
+            continue;
+        } else if path.extension().is_some_and(|ext| {
+            SBUILD_EXTENSIONS
+                .iter()
+                .any(|e| ext.eq_ignore_ascii_case(e))
+        }) {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+}
+
+// Reads newline-separated paths, e.g. a generated file list, skipping blank
+// lines and `#` comments. Paths are taken as they are, relative to the
+// current directory.
+pub fn read_path_list<R: BufRead>(reader: R) -> io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let path = line.trim();
+        if path.is_empty() || path.starts_with('#') {
+            continue;
+        }
+        paths.push(path.to_string());
+    }
+    Ok(paths)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+
+    #[test]
+    fn symlinked_directories_are_not_followed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pkgs = dir.path().join("pkgs");
+        fs::create_dir(&pkgs).unwrap();
+        fs::write(pkgs.join("a.yaml"), "").unwrap();
+        fs::write(dir.path().join("target.yml"), "").unwrap();
+        symlink(dir.path(), pkgs.join("loop")).unwrap();
+        symlink(dir.path().join("target.yml"), pkgs.join("link.yml")).unwrap();
+
+        let root = dir.path().to_string_lossy().into_owned();
+        let files = expand_paths(vec![root.clone()], None);
+        assert_eq!(
+            files,
+            [
+                format!("{}/pkgs/a.yaml", root),
+                format!("{}/pkgs/link.yml", root),
+                format!("{}/target.yml", root),
+            ]
+        );
+    }
+}
@@ -1,7 +1,4 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter, Write},
-};
+use std::io::{self, Write};
 
 use indexmap::IndexMap;
 use serde::{
@@ -36,6 +33,14 @@ impl<'de> Visitor<'de> for DistroPkgVisitor {
         Ok(DistroPkg::List(vec))
     }
 
+    // an entry without a value (`x86_64:`) is treated as an empty list
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(DistroPkg::List(Vec::new()))
+    }
+
     fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
     where
         A: de::MapAccess<'de>,
@@ -56,7 +61,7 @@ impl<'de> Deserialize<'de> for DistroPkg {
 }
 
 impl DistroPkg {
-    pub fn write_yaml(&self, writer: &mut BufWriter<File>, indent: usize) -> io::Result<()> {
+    pub fn write_yaml<W: Write>(&self, writer: &mut W, indent: usize) -> io::Result<()> {
         let indent_str = " ".repeat(indent);
 
         match self {
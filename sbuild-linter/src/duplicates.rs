@@ -0,0 +1,65 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Mutex,
+};
+
+use indexmap::IndexMap;
+
+use crate::{build_config::BuildConfig, comments::Comments};
+
+// Files whose configs are identical once normalized, across a batch. Configs
+// are compared by the YAML the linter writes for them, without comments and
+// with the fields in their usual order, so files differing only in
+// formatting, quoting or comments are still found.
+#[derive(Default)]
+pub struct DuplicateConfigs {
+    // hash of the normalized config -> files having it, in the order they
+    // were recorded
+    configs: Mutex<IndexMap<u64, Vec<String>>>,
+}
+
+impl DuplicateConfigs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, file_path: &str, config: &BuildConfig) {
+        let mut normalized = Vec::new();
+        if config
+            .write_yaml(&mut normalized, 0, Comments::new())
+            .is_err()
+        {
+            return;
+        }
+        let mut hasher = DefaultHasher::new();
+        normalized.hash(&mut hasher);
+
+        if let Ok(mut configs) = self.configs.lock() {
+            configs
+                .entry(hasher.finish())
+                .or_default()
+                .push(file_path.to_string());
+        }
+    }
+
+    // Returns every group of files sharing a config, with sorted files and
+    // groups sorted by their first file so the report doesn't depend on the
+    // order the jobs finished in.
+    pub fn groups(&self) -> Vec<Vec<String>> {
+        let Ok(configs) = self.configs.lock() else {
+            return Vec::new();
+        };
+
+        let mut groups: Vec<Vec<String>> = configs
+            .values()
+            .filter(|files| files.len() > 1)
+            .map(|files| {
+                let mut files = files.clone();
+                files.sort();
+                files
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+}
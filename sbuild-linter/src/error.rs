@@ -1,27 +1,83 @@
-use colored::Colorize;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
 
 use crate::logger::TaskLogger;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     Warn,
     Error,
 }
 
-#[derive(Debug)]
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+}
+
+// Lowest severity that makes a file fail, or `None` to never fail the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailOn {
+    Error,
+    Warn,
+    None,
+}
+
+impl FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(FailOn::Error),
+            "warn" => Ok(FailOn::Warn),
+            "none" => Ok(FailOn::None),
+            _ => Err(format!("Invalid severity: '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ErrorDetails {
+    pub rule: &'static str,
     pub field: String,
     pub message: String,
     pub line_number: usize,
     pub severity: Severity,
 }
 
-pub fn highlight_error_line(
+// Wraps the text in the given SGR codes. Rendering doesn't go through
+// `colored` so that the output only depends on `use_color`.
+fn paint(text: &str, codes: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", codes, text)
+}
+
+pub fn render_error(error: &ErrorDetails, use_color: bool) -> String {
+    if !use_color {
+        return format!("{} -> {}", error.field, error.message);
+    }
+    let color = match error.severity {
+        Severity::Error => "31",
+        Severity::Warn => "33",
+    };
+    format!(
+        "{} -> {}",
+        paint(&error.field, "1"),
+        paint(&error.message, color)
+    )
+}
+
+// Renders the lines surrounding `line_number`, marking the offending line.
+pub fn render_error_line(
     yaml_str: &str,
     line_number: usize,
     is_fatal: bool,
-    logger: &TaskLogger,
-) {
+    use_color: bool,
+) -> String {
     let context_range = 3;
     let start_line = line_number.saturating_sub(context_range);
     let end_line = if line_number + context_range < yaml_str.lines().count() {
@@ -30,27 +86,112 @@ pub fn highlight_error_line(
         yaml_str.lines().count()
     };
 
-    let lines: Vec<&str> = yaml_str
+    let mut rendered = Vec::new();
+    for (index, line) in yaml_str
         .lines()
         .skip(start_line)
-        .take(end_line - start_line)
-        .collect();
-
-    for (index, line) in lines.iter().enumerate() {
+        .take(end_line.saturating_sub(start_line))
+        .enumerate()
+    {
         let current_line_number = start_line + index + 1;
         if current_line_number == line_number {
             let msg = format!("--> {}: {}", current_line_number, line);
-            logger.custom_error(&format!(
-                "{}",
-                if is_fatal {
-                    msg.red().bold()
-                } else {
-                    msg.yellow().bold()
-                }
-            ));
+            rendered.push(if !use_color {
+                msg
+            } else if is_fatal {
+                paint(&msg, "1;31")
+            } else {
+                paint(&msg, "1;33")
+            });
         } else {
-            logger.custom_error(&format!("    {}: {}", current_line_number, line));
+            rendered.push(format!("    {}: {}", current_line_number, line));
         }
     }
+    rendered.join("\n")
+}
+
+pub fn highlight_error_line(
+    yaml_str: &str,
+    line_number: usize,
+    is_fatal: bool,
+    use_color: bool,
+    logger: &TaskLogger,
+) {
+    let rendered = render_error_line(yaml_str, line_number, is_fatal, use_color);
+    if !rendered.is_empty() {
+        logger.custom_error(rendered);
+    }
     logger.custom_error("");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "pkg: foo\ndescription: bar\nsrc_url: baz";
+
+    fn error(severity: Severity) -> ErrorDetails {
+        ErrorDetails {
+            rule: "invalid-type",
+            field: "pkg".to_string(),
+            message: "Invalid value".to_string(),
+            line_number: 2,
+            severity,
+        }
+    }
+
+    #[test]
+    fn render_error_without_color() {
+        assert_eq!(
+            render_error(&error(Severity::Error), false),
+            "pkg -> Invalid value"
+        );
+        assert_eq!(
+            render_error(&error(Severity::Warn), false),
+            "pkg -> Invalid value"
+        );
+    }
+
+    #[test]
+    fn render_error_with_color() {
+        assert_eq!(
+            render_error(&error(Severity::Error), true),
+            "\x1b[1mpkg\x1b[0m -> \x1b[31mInvalid value\x1b[0m"
+        );
+        assert_eq!(
+            render_error(&error(Severity::Warn), true),
+            "\x1b[1mpkg\x1b[0m -> \x1b[33mInvalid value\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn render_error_line_without_color() {
+        assert_eq!(
+            render_error_line(SOURCE, 2, true, false),
+            "    1: pkg: foo\n--> 2: description: bar\n    3: src_url: baz"
+        );
+    }
+
+    #[test]
+    fn render_error_line_with_color() {
+        assert_eq!(
+            render_error_line(SOURCE, 2, true, true),
+            "    1: pkg: foo\n\x1b[1;31m--> 2: description: bar\x1b[0m\n    3: src_url: baz"
+        );
+        assert_eq!(
+            render_error_line(SOURCE, 2, false, true),
+            "    1: pkg: foo\n\x1b[1;33m--> 2: description: bar\x1b[0m\n    3: src_url: baz"
+        );
+    }
+
+    #[test]
+    fn rendering_ignores_the_global_color_override() {
+        colored::control::set_override(false);
+        let colored = render_error(&error(Severity::Error), true);
+        colored::control::set_override(true);
+        let plain = render_error(&error(Severity::Error), false);
+        colored::control::unset_override();
+        assert!(colored.contains('\x1b'));
+        assert!(!plain.contains('\x1b'));
+    }
+}
@@ -1,34 +1,64 @@
 use std::{
-    collections::HashSet,
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
     env,
     fmt::Display,
-    fs::{File, Permissions},
-    io::{BufRead, BufReader, BufWriter, Write},
+    fs::{self, File, Permissions},
+    io::{self, BufReader, BufWriter, Read, Write},
     os::unix::fs::PermissionsExt,
-    path::Path,
-    process::{Command, ExitStatus},
-    sync, thread,
-    time::Duration,
+    path::{Component, Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    sync::{self, Arc},
+    thread,
+    time::{Duration, Instant},
 };
 
 use build_config::{visitor::BuildConfigVisitor, BuildConfig};
+use cache::Cache;
 use colored::Colorize;
 use comments::Comments;
-use logger::TaskLogger;
-use serde::{Deserialize, Deserializer};
+
+use error::{highlight_error_line, ErrorDetails, Severity};
+use logger::{LogManager, TaskLogger};
+use oplog::OpLog;
+use profile::Profile;
+use report::Report;
+use serde::{de, Deserialize, Deserializer};
+use shapes::FieldShapes;
+use shellcheck::{Finding, Outcome, ScriptLocation};
 use tempfile::NamedTempFile;
+use url_check::{UrlChecker, UrlResponse};
 
+pub mod anchors;
+pub mod blame;
 pub mod build_config;
+pub mod cache;
+pub mod checkpoint;
 pub mod comments;
 pub mod description;
+pub mod diff;
 pub mod disabled;
+pub mod discover;
 pub mod distro_pkg;
+pub mod duplicates;
 pub mod error;
 pub mod license;
 pub mod logger;
+pub mod oplog;
+pub mod profile;
+pub mod report;
 pub mod resource;
+pub mod rules;
 pub mod semaphore;
+pub mod shapes;
+pub mod shell_options;
+pub mod shellcheck;
+pub mod unique;
+pub mod url_check;
 pub mod validator;
+pub mod variables;
+pub mod whitespace;
 pub mod xexec;
 
 pub const VALID_PKG_TYPES: [&str; 9] = [
@@ -43,7 +73,39 @@ pub const VALID_PKG_TYPES: [&str; 9] = [
     "static",
 ];
 pub const VALID_CATEGORIES: &str = include_str!("categories");
+// the main categories of the freedesktop menu specification, used as the
+// primary categories when none are given
+pub const DEFAULT_PRIMARY_CATEGORIES: &str = "AudioVideo,Audio,Video,Development,Education,Game,\
+Graphics,Network,Office,Science,Settings,System,Utility";
+// the keys of the fragment or query parameter pinning a git `src_url` to a
+// revision, e.g. `#tag=v1.0`, used when none are given
+pub const DEFAULT_GIT_PIN_KEYS: &str = "tag,commit";
+// sites icons may be hosted on whatever the homepage, used when none are
+// given
+pub const DEFAULT_ICON_HOSTS: &str = "githubusercontent.com";
+// shell options `x_exec.run` must enable before its first command, used when
+// none are given
+pub const DEFAULT_SET_OPTIONS: &str = "errexit";
+// patterns of lines exempt from `--max-line-length`, i.e. the ones
+// containing a URL, used when none are given
+pub const DEFAULT_LINE_LENGTH_EXEMPT: &str = "://";
+// markers of text left over from a template in free-text fields, used when
+// none are given
+pub const DEFAULT_PLACEHOLDERS: &str = "TODO,FIXME,TBD,XXX,lorem ipsum,description here";
+// the order fields are written in, also used by `--field-order` when no
+// order is given
+pub const DEFAULT_FIELD_ORDER: &str =
+    "format_version,_disabled,_disabled_reason,pkg,pkg_id,pkg_type,pkgver,\
+app_id,build_util,build_asset,category,description,distro_pkg,homepage,maintainer,icon,desktop,\
+license,note,provides,repology,src_url,tag,x_exec";
+
 pub const VALID_ARCH: [&str; 4] = ["aarch64", "loongarch64", "riscv64", "x86_64"];
+// architecture names that show up in distro_pkg but can't be built for,
+// including the distro-specific aliases of the supported ones
+pub const UNSUPPORTED_ARCH: [&str; 13] = [
+    "amd64", "arm64", "armel", "armhf", "armv7", "armv7l", "i386", "i686", "mips64", "ppc64",
+    "ppc64le", "s390x", "x86",
+];
 pub const VALID_OS: [&str; 6] = ["freebsd", "illumos", "linux", "netbsd", "openbsd", "redox"];
 
 #[derive(Debug, Deserialize, Clone)]
@@ -52,14 +114,310 @@ pub struct BuildAsset {
     pub out: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Auto,
+    Json,
+    Yaml,
+}
+
+impl InputFormat {
+    // `Auto` picks JSON for `.json` files or content starting with `{`,
+    // and YAML otherwise.
+    pub fn resolve(self, file_path: &str, content: &str) -> InputFormat {
+        match self {
+            InputFormat::Auto => {
+                let is_json = Path::new(file_path)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+                    || content.trim_start().starts_with('{');
+                if is_json {
+                    InputFormat::Json
+                } else {
+                    InputFormat::Yaml
+                }
+            }
+            format => format,
+        }
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(InputFormat::Auto),
+            "json" => Ok(InputFormat::Json),
+            "yaml" => Ok(InputFormat::Yaml),
+            _ => Err(format!("Invalid input format: '{}'", s)),
+        }
+    }
+}
+
+// How many `src_url` entries fit the architectures `distro_pkg` has packages
+// for: a single source shared by all of them or one per architecture, or only
+// one per architecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchSources {
+    SharedOrPerArch,
+    PerArch,
+}
+
+impl ArchSources {
+    pub fn fits(&self, src_urls: usize, archs: usize) -> bool {
+        match self {
+            ArchSources::SharedOrPerArch => src_urls == 1 || src_urls == archs,
+            ArchSources::PerArch => src_urls == archs,
+        }
+    }
+}
+
+impl FromStr for ArchSources {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shared-or-per-arch" => Ok(ArchSources::SharedOrPerArch),
+            "per-arch" => Ok(ArchSources::PerArch),
+            _ => Err(format!("Invalid arch sources rule: '{}'", s)),
+        }
+    }
+}
+
+// media types of web pages, which homepages are expected to be served as
+pub const HTML_CONTENT_TYPES: [&str; 2] = ["text/html", "application/xhtml+xml"];
+
+// version of the SBUILD format implemented by the linter, assumed for files
+// without `format_version`, and the oldest version that is still understood
+pub const FORMAT_VERSION: u64 = 1;
+pub const MIN_FORMAT_VERSION: u64 = 1;
+
+// name content linted with `lint_string` is reported under
+pub const IN_MEMORY_PATH: &str = "<string>";
+
+pub const DEFAULT_MAX_FIELDS: usize = 64;
+pub const DEFAULT_MAX_CATEGORIES: usize = 8;
+pub const DEFAULT_MAX_SIZE: usize = 512 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    // report unknown fields as errors instead of warnings
+    pub strict_fields: bool,
+    // report descriptions spanning several lines as errors instead of
+    // warnings
+    pub strict_description: bool,
+    // number of top-level entries and document size in bytes above which the
+    // config is likely generated by mistake
+    pub max_fields: usize,
+    pub max_size: usize,
+    // number of categories above which the package is likely over-tagged
+    pub max_categories: usize,
+    // run the pkgver script twice and warn if the results differ
+    pub verify_pkgver: bool,
+    // correct the issues that can be fixed automatically in the validated
+    // output
+    pub fix: bool,
+    // categories the first entry of `category` must be one of
+    pub primary_categories: Option<Vec<String>>,
+    // add who last changed the line to the diagnostics of the visitor
+    pub vcs_blame: bool,
+    // variables the scripts may use besides the ones provided by sbuild
+    pub allowed_vars: Vec<String>,
+    // order the fields are expected in, and written in with `fix`
+    pub field_order: Option<Vec<String>>,
+    // keys pinning git sources to a revision, required if set
+    pub git_pin_keys: Option<Vec<String>>,
+    // sites the icon may be hosted on besides the homepage's, checked if set
+    pub icon_hosts: Option<Vec<String>>,
+    // warn about homepages not served as web pages when checking URLs
+    pub homepage_content_type: bool,
+    // how many `src_url` entries the architectures of `distro_pkg` need,
+    // checked if set
+    pub arch_sources: Option<ArchSources>,
+    // shell options `x_exec.run` must enable at its top, checked if set
+    pub set_options: Option<Vec<String>>,
+    // markers of template text that must not be left in free-text fields
+    pub placeholders: Vec<String>,
+    // lowest severity of shellcheck findings that fails the file
+    pub shellcheck_severity: shellcheck::Level,
+    // `(pkg_type, category)` pairs that don't fit together, replacing the
+    // built-in ones if set
+    pub incoherent_categories: Option<Vec<(String, String)>>,
+    // `pkg_type` values needing `app_id`, replacing the built-in desktop
+    // image formats if set
+    pub gui_types: Option<Vec<String>>,
+    // length in characters above which source lines are reported, and the
+    // patterns of lines that may be longer
+    pub max_line_length: Option<usize>,
+    pub line_length_exempt: Vec<String>,
+    // render the diagnostics with ANSI colors
+    pub color: bool,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            strict_fields: false,
+            strict_description: false,
+            max_fields: DEFAULT_MAX_FIELDS,
+            max_size: DEFAULT_MAX_SIZE,
+            max_categories: DEFAULT_MAX_CATEGORIES,
+            verify_pkgver: false,
+            fix: false,
+            primary_categories: None,
+            vcs_blame: false,
+            allowed_vars: Vec::new(),
+            field_order: None,
+            git_pin_keys: None,
+            icon_hosts: None,
+            homepage_content_type: false,
+            arch_sources: None,
+            set_options: None,
+            placeholders: DEFAULT_PLACEHOLDERS.split(',').map(String::from).collect(),
+            shellcheck_severity: shellcheck::Level::Error,
+            incoherent_categories: None,
+            gui_types: None,
+            max_line_length: None,
+            line_length_exempt: DEFAULT_LINE_LENGTH_EXEMPT
+                .split(',')
+                .map(String::from)
+                .collect(),
+            color: true,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Linter {
     logger: TaskLogger,
     timeout: Duration,
+    profile: Option<Arc<Profile>>,
+    input_format: InputFormat,
+    url_checker: Option<Arc<UrlChecker>>,
+    report: Option<Arc<Report>>,
+    field_shapes: Option<Arc<FieldShapes>>,
+    out_dir: Option<PathBuf>,
+    // print the validated YAML instead of writing it to a file
+    to_stdout: bool,
+    // print the parsed config of the files passing validation, for debugging
+    dump_config: bool,
+    cache: Option<Arc<Cache>>,
+    op_log: Option<Arc<OpLog>>,
+    options: LintOptions,
 }
 
 impl Linter {
     pub fn new(logger: TaskLogger, timeout: Duration) -> Self {
-        Linter { logger, timeout }
+        Linter {
+            logger,
+            timeout,
+            profile: None,
+            input_format: InputFormat::Auto,
+            url_checker: None,
+            report: None,
+            field_shapes: None,
+            out_dir: None,
+            to_stdout: false,
+            dump_config: false,
+            cache: None,
+            op_log: None,
+            options: LintOptions::default(),
+        }
+    }
+
+    pub fn with_out_dir(mut self, out_dir: Option<PathBuf>) -> Self {
+        self.out_dir = out_dir;
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Option<Arc<Cache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_op_log(mut self, op_log: Option<Arc<OpLog>>) -> Self {
+        self.op_log = op_log;
+        self
+    }
+
+    pub fn with_stdout(mut self, to_stdout: bool) -> Self {
+        self.to_stdout = to_stdout;
+        self
+    }
+
+    pub fn with_dump_config(mut self, dump_config: bool) -> Self {
+        self.dump_config = dump_config;
+        self
+    }
+
+    pub fn with_report(mut self, report: Option<Arc<Report>>) -> Self {
+        self.report = report;
+        self
+    }
+
+    pub fn with_field_shapes(mut self, field_shapes: Option<Arc<FieldShapes>>) -> Self {
+        self.field_shapes = field_shapes;
+        self
+    }
+
+    pub fn with_options(mut self, options: LintOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_url_checker(mut self, url_checker: Option<Arc<UrlChecker>>) -> Self {
+        self.url_checker = url_checker;
+        self
+    }
+
+    pub fn with_input_format(mut self, input_format: InputFormat) -> Self {
+        self.input_format = input_format;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: Option<Arc<Profile>>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    fn record_phase(&self, file_path: &str, phase: &'static str, start: Instant) {
+        if let Some(ref profile) = self.profile {
+            profile.record(file_path, phase, start);
+        }
+        if let Some(ref op_log) = self.op_log {
+            op_log.debug(format!(
+                "{}: {} took {:?}",
+                file_path,
+                phase,
+                start.elapsed()
+            ));
+        }
+    }
+
+    // Records a diagnostic raised outside of the visitor, which reports its
+    // own errors.
+    fn record_diagnostic(
+        &self,
+        file_path: &str,
+        rule: &'static str,
+        field: &str,
+        message: String,
+        line_number: usize,
+        severity: Severity,
+    ) {
+        if let Some(ref report) = self.report {
+            report.record(
+                file_path,
+                [ErrorDetails {
+                    rule,
+                    field: field.to_string(),
+                    message,
+                    line_number,
+                    severity,
+                }],
+            );
+        }
     }
 
     pub fn lint(
@@ -70,13 +428,28 @@ impl Linter {
         pkgver: bool,
     ) -> Option<BuildConfig> {
         let logger = &self.logger;
-        let yaml_str = match self.read_yaml(file_path) {
-            Ok(y) => y,
+        if let Some(ref report) = self.report {
+            report.add_file(file_path);
+        }
+
+        let start = Instant::now();
+        let (yaml_str, input_format) = match self.read_source(file_path) {
+            Ok(source) => source,
             Err(err) => {
-                eprintln!("{}", err);
+                let msg = err.to_string().trim_end().to_string();
+                logger.custom_error(&msg);
+                self.record_diagnostic(
+                    file_path,
+                    "unreadable-file",
+                    "file",
+                    msg,
+                    0,
+                    Severity::Error,
+                );
                 return None;
             }
         };
+        self.record_phase(file_path, "read", start);
 
         let path = Path::new(&file_path);
         let real_path = if path.is_absolute() {
@@ -85,218 +458,897 @@ impl Linter {
             let current_dir = env::current_dir().expect("Failed to get current directory");
             &current_dir.join(path)
         };
-        logger.info(format!("Linting {} ({})\n", file_path, real_path.display()));
-        match self.deserialize_yaml(&yaml_str) {
-            Ok(config) => {
+        logger.heading(format!("Linting {} ({})\n", file_path, real_path.display()));
+        let start = Instant::now();
+        let result = self.deserialize(file_path, &yaml_str, input_format);
+        self.record_phase(file_path, "parse", start);
+        match result {
+            Ok(mut config) => {
+                self.check_script_whitespace(file_path, &yaml_str, input_format, &mut config);
+                self.check_file_indentation(file_path, &yaml_str, input_format, &mut config);
+                self.check_script_variables(file_path, &yaml_str, input_format, &config);
+                self.check_set_options(file_path, &yaml_str, input_format, &config);
+                if self.dump_config {
+                    // after the fixes, so it is what the later checks and the
+                    // validated output see
+                    logger.dump(format!("{}: {:#?}", file_path, config));
+                }
                 if disable_shellcheck {
                     logger.info("Skipping shellcheck");
                 } else {
                     logger.info("Performing shellcheck");
-                    if !self.is_shellcheck_success(&config) {
+                    let start = Instant::now();
+                    let shellcheck_success =
+                        self.is_shellcheck_success(file_path, &yaml_str, input_format, &config);
+                    self.record_phase(file_path, "shellcheck", start);
+                    if !shellcheck_success {
                         return None;
                     }
                     logger.success("Shellcheck passed");
                 }
+                if let Some(ref url_checker) = self.url_checker {
+                    logger.info("Checking URL reachability");
+                    let start = Instant::now();
+                    let urls_success = self.check_urls(file_path, url_checker, &config);
+                    self.record_phase(file_path, "url-check", start);
+                    if !urls_success {
+                        return None;
+                    }
+                }
                 if let Some(pkgver_path) = pkgver.then(|| format!("{}.pkgver", file_path)) {
-                    if !self.generate_pkgver(&config, &pkgver_path) {
+                    let start = Instant::now();
+                    let pkgver_success = self.generate_pkgver(&config, &pkgver_path);
+                    if pkgver_success && self.options.verify_pkgver {
+                        self.is_pkgver_stable(file_path, &config, &pkgver_path);
+                    }
+                    self.record_phase(file_path, "pkgver", start);
+                    if !pkgver_success {
+                        self.record_diagnostic(
+                            file_path,
+                            "pkgver",
+                            "x_exec.pkgver",
+                            "Failed to determine pkgver".to_string(),
+                            0,
+                            Severity::Error,
+                        );
                         return None;
                     }
                 };
 
+                let start = Instant::now();
                 let mut comments = Comments::new();
-                comments.parse_comments(file_path).unwrap();
+                if input_format == InputFormat::Yaml {
+                    comments.parse_comments(file_path).unwrap();
+                }
 
-                let output_path = inplace
-                    .then_some(file_path.to_string())
-                    .unwrap_or_else(|| format!("{}.validated", file_path));
+                if self.to_stdout {
+                    let mut yaml = Vec::new();
+                    self.write_config(&config, &mut yaml, comments).unwrap();
+                    self.record_phase(file_path, "write", start);
+                    logger.success("SBUILD validation successful.");
+                    // sent as a whole so documents of parallel jobs don't
+                    // interleave
+                    let yaml = String::from_utf8_lossy(&yaml);
+                    logger.result(format!("---\n{}", yaml.trim_end()));
+                    return Some(config);
+                }
+
+                let output_path = if let Some(ref out_dir) = self.out_dir {
+                    let mut output_path = out_dir.join(mirrored_path(file_path));
+                    // the validated output is always YAML
+                    if input_format == InputFormat::Json {
+                        output_path.set_extension("yaml");
+                    }
+                    if let Some(parent) = output_path.parent() {
+                        if let Err(err) = fs::create_dir_all(parent) {
+                            logger.error(format!(
+                                "Failed to create directory {}: {}",
+                                parent.display(),
+                                err
+                            ));
+                            return None;
+                        }
+                    }
+                    output_path.to_string_lossy().into_owned()
+                } else if inplace {
+                    file_path.to_string()
+                } else {
+                    format!("{}.validated", file_path)
+                };
                 let file = File::create(&output_path).unwrap();
                 let mut writer = BufWriter::new(file);
 
-                config.write_yaml(&mut writer, 0, comments).unwrap();
-                logger.info("SBUILD validation successful.");
-                logger.info(&format!(
+                self.write_config(&config, &mut writer, comments).unwrap();
+                self.record_phase(file_path, "write", start);
+                logger.success("SBUILD validation successful.");
+                logger.info(format!(
                     "Validated YAML has been written to {}",
                     output_path
                 ));
                 return Some(config);
             }
-            Err(_) => {
+            Err(err) => {
+                // errors from the visitor are already reported, so only the
+                // ones raised by the parser itself carry a message
+                let msg = err.to_string();
+                if !msg.is_empty() {
+                    logger.error(&msg);
+                    self.record_diagnostic(
+                        file_path,
+                        "syntax-error",
+                        "document",
+                        msg,
+                        0,
+                        Severity::Error,
+                    );
+                }
                 logger.error("SBUILD validation failed.");
             }
         };
         None
     }
 
-    fn deserialize_yaml(&self, yaml_str: &str) -> Result<BuildConfig, serde_yml::Error> {
-        let deserializer = serde_yml::Deserializer::from_str(yaml_str);
-        let visitor = BuildConfigVisitor {
+    // Runs the checks that only depend on the content, i.e. the visitor and
+    // the script checks, on `content` as if it were read from `file_path`.
+    // Nothing is executed or written, and the diagnostics go to the report as
+    // usual.
+    pub fn lint_str(&self, file_path: &str, content: &str) -> Option<BuildConfig> {
+        if let Some(ref report) = self.report {
+            report.add_file(file_path);
+        }
+
+        let (yaml_str, input_format) = match self.split_source(file_path, content.to_string()) {
+            Ok(source) => source,
+            Err(err) => {
+                self.record_diagnostic(
+                    file_path,
+                    "unreadable-file",
+                    "file",
+                    err.to_string().trim_end().to_string(),
+                    0,
+                    Severity::Error,
+                );
+                return None;
+            }
+        };
+        match self.deserialize(file_path, &yaml_str, input_format) {
+            Ok(mut config) => {
+                self.check_script_whitespace(file_path, &yaml_str, input_format, &mut config);
+                self.check_file_indentation(file_path, &yaml_str, input_format, &mut config);
+                self.check_script_variables(file_path, &yaml_str, input_format, &config);
+                self.check_set_options(file_path, &yaml_str, input_format, &config);
+                Some(config)
+            }
+            Err(err) => {
+                let msg = err.to_string();
+                if !msg.is_empty() {
+                    self.record_diagnostic(
+                        file_path,
+                        "syntax-error",
+                        "document",
+                        msg,
+                        0,
+                        Severity::Error,
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    // Validates the content like `lint_str` and returns the typed config if
+    // it passed, or every diagnostic if it didn't. The warnings of content
+    // that passed are dropped, `lint_string` returns them.
+    pub fn parse(&self, content: &str) -> Result<BuildConfig, Vec<ErrorDetails>> {
+        let (config, errors) = self.lint_collect(content);
+        config.ok_or(errors)
+    }
+
+    // Lints the content with a report of its own, so the diagnostics can be
+    // returned whether or not the linter has a report.
+    fn lint_collect(&self, content: &str) -> (Option<BuildConfig>, Vec<ErrorDetails>) {
+        let report = Arc::new(Report::new());
+        let config = self
+            .clone()
+            .with_report(Some(report.clone()))
+            .lint_str(IN_MEMORY_PATH, content);
+        (config, report.take_errors(IN_MEMORY_PATH))
+    }
+
+    fn deserialize(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+    ) -> Result<BuildConfig, Box<dyn std::error::Error>> {
+        let mut visitor = BuildConfigVisitor {
             sbuild_str: yaml_str.to_string(),
+            input_format,
+            options: self.options.clone(),
+            file_path: file_path.to_string(),
+            report: self.report.clone(),
+            field_shapes: self.field_shapes.clone(),
             visited: HashSet::new(),
             errors: Vec::new(),
+            blame: OnceCell::new(),
+            key_lines: OnceCell::new(),
             logger: self.logger.clone(),
         };
-        deserializer.deserialize_map(visitor)
+
+        if input_format == InputFormat::Json {
+            let mut deserializer = serde_json::Deserializer::from_str(yaml_str);
+            return Ok(deserializer.deserialize_any(visitor)?);
+        }
+
+        // undefined aliases and further documents make the document
+        // unloadable, so they need to be reported before handing it over to
+        // serde
+        visitor.validate_documents();
+        visitor.validate_anchors();
+        if visitor.has_fatal_errors() {
+            visitor.report_errors();
+            return Err(Box::new(<serde_yml::Error as de::Error>::custom("")));
+        }
+        visitor.validate_duplicate_keys();
+
+        let deserializer = serde_yml::Deserializer::from_str(yaml_str);
+        Ok(deserializer.deserialize_any(visitor)?)
+    }
+
+    fn read_source(&self, file_path: &str) -> Result<(String, InputFormat), FileError> {
+        let file = File::open(file_path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => FileError::NotFound(file_path.into()),
+            _ => FileError::Unreadable(file_path.into(), err),
+        })?;
+        let mut reader = BufReader::new(file);
+
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::InvalidData => FileError::InvalidFile(file_path.into()),
+                _ => FileError::Unreadable(file_path.into(), err),
+            })?;
+
+        self.split_source(file_path, content)
     }
 
-    fn read_yaml(&self, file_path: &str) -> Result<String, FileError> {
+    // Resolves the format of the content and strips the shebang line of YAML
+    // documents, warning if it's missing.
+    fn split_source(
+        &self,
+        file_path: &str,
+        content: String,
+    ) -> Result<(String, InputFormat), FileError> {
         let logger = &self.logger;
-        let Ok(file) = File::open(file_path) else {
-            return Err(FileError::NotFound(file_path.into()));
-        };
-        let reader = BufReader::new(file);
+        let input_format = self.input_format.resolve(file_path, &content);
+        if input_format == InputFormat::Json {
+            return Ok((content, input_format));
+        }
 
         let mut yaml_content = String::new();
-        let mut lines = reader.lines();
+        let mut lines = content.lines();
 
         if let Some(line) = lines.next() {
-            let line = line.map_err(|_| FileError::InvalidFile(file_path.into()))?;
             if !line.trim_start().starts_with("#!/SBUILD") {
                 logger.warn("File doesn't start with '#!/SBUILD'");
+                self.record_diagnostic(
+                    file_path,
+                    "missing-shebang",
+                    "document",
+                    "File doesn't start with '#!/SBUILD'".to_string(),
+                    1,
+                    Severity::Warn,
+                );
             }
         } else {
             return Err(FileError::InvalidFile(file_path.into()));
         }
 
         for line in lines {
-            let line = line.map_err(|_| FileError::InvalidFile(file_path.into()))?;
-            yaml_content.push_str(&line);
+            yaml_content.push_str(line);
             yaml_content.push('\n');
         }
 
-        Ok(yaml_content)
+        Ok((yaml_content, input_format))
     }
 
-    fn run_shellcheck(&self, script: &str, severity: &str) -> std::io::Result<ExitStatus> {
-        let tmp = temp_script_file(script);
+    // Warns about CRLF line endings and mixed indentation in the scripts, and
+    // normalizes them with `--fix` before they are checked and written out.
+    fn check_script_whitespace(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        config: &mut BuildConfig,
+    ) {
+        let logger = &self.logger;
+        let x_exec = &mut config.x_exec;
+        let scripts = [
+            ("run", Some(&mut x_exec.run)),
+            ("pkgver", x_exec.pkgver.as_mut()),
+        ];
+
+        for (key, script) in scripts {
+            let Some(script) = script else {
+                continue;
+            };
+            let issues = whitespace::check(script);
+            if issues.is_empty() {
+                continue;
+            }
 
-        let out = Command::new("shellcheck")
-            .arg(format!("--severity={}", severity))
-            .arg(tmp.path())
-            .status();
+            let field = format!("x_exec.{}", key);
+            let location = ScriptLocation::find(yaml_str, input_format == InputFormat::Json, key);
+            for issue in issues {
+                logger.warn(format!("{} -> {}", field.bold(), issue.message));
+
+                // the script line is shifted by one, as `source_line` expects
+                // the line numbers of the script passed to shellcheck
+                let line_number = location.source_line(issue.line + 1);
+                if line_number != 0 {
+                    highlight_error_line(yaml_str, line_number, false, self.options.color, logger);
+                }
+                let file_line = if line_number != 0 && input_format != InputFormat::Json {
+                    line_number + 1
+                } else {
+                    line_number
+                };
+                self.record_diagnostic(
+                    file_path,
+                    issue.rule,
+                    &field,
+                    issue.message,
+                    file_line,
+                    Severity::Warn,
+                );
+            }
 
-        tmp.close()?;
-        out
+            if self.options.fix {
+                *script = whitespace::normalize(script);
+                logger.info(format!("Normalized whitespace in {}", field));
+            }
+        }
     }
 
-    fn shellcheck(&self, script: &str) -> std::io::Result<()> {
-        if !self.run_shellcheck(script, "error")?.success() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Shellcheck emitted errors.",
-            ));
+    // Warns when the document as a whole mixes tabs and spaces for
+    // indentation, and with `--fix` converts the tabs in the scripts, where
+    // they can only occur, to spaces.
+    fn check_file_indentation(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        config: &mut BuildConfig,
+    ) {
+        let Some(issue) = whitespace::check_file(yaml_str) else {
+            return;
+        };
+        let logger = &self.logger;
+        logger.warn(format!("{} -> {}", "document".bold(), issue.message));
+        highlight_error_line(yaml_str, issue.line, false, self.options.color, logger);
+        let file_line = if input_format == InputFormat::Json {
+            issue.line
+        } else {
+            issue.line + 1
+        };
+        self.record_diagnostic(
+            file_path,
+            issue.rule,
+            "document",
+            issue.message,
+            file_line,
+            Severity::Warn,
+        );
+
+        if !self.options.fix {
+            return;
+        }
+        let x_exec = &mut config.x_exec;
+        let scripts = [
+            ("run", Some(&mut x_exec.run)),
+            ("pkgver", x_exec.pkgver.as_mut()),
+        ];
+        for (key, script) in scripts {
+            // `<<-` heredocs rely on leading tabs being stripped
+            let Some(script) = script.filter(|script| !script.contains("<<-")) else {
+                continue;
+            };
+            let expanded = whitespace::expand_tabs(script);
+            if expanded != *script {
+                *script = expanded;
+                logger.info(format!("Converted tabs to spaces in x_exec.{}", key));
+            }
+        }
+    }
+
+    fn write_config<W: Write>(
+        &self,
+        config: &BuildConfig,
+        writer: &mut W,
+        comments: Comments,
+    ) -> io::Result<()> {
+        match (self.options.fix, &self.options.field_order) {
+            (true, Some(order)) => config.write_yaml_ordered(writer, 0, comments, order),
+            _ => config.write_yaml(writer, 0, comments),
+        }
+    }
+
+    // Warns about variables the scripts use without them being provided by
+    // sbuild or assigned in the script, which are usually misspelled.
+    fn check_script_variables(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        config: &BuildConfig,
+    ) {
+        let logger = &self.logger;
+        let allowed = &self.options.allowed_vars;
+        let x_exec = &config.x_exec;
+        let scripts = [
+            ("run", Some(&x_exec.run)),
+            ("pkgver", x_exec.pkgver.as_ref()),
+        ];
+
+        for (key, script) in scripts {
+            let Some(script) = script else {
+                continue;
+            };
+            let references = variables::check(script, allowed);
+            if references.is_empty() {
+                continue;
+            }
+
+            let field = format!("x_exec.{}", key);
+            let location = ScriptLocation::find(yaml_str, input_format == InputFormat::Json, key);
+            for reference in references {
+                let message = match variables::suggest(&reference.name, allowed) {
+                    Some(suggestion) => format!(
+                        "${} is not provided by sbuild or assigned in the script. Did you mean ${}?",
+                        reference.name, suggestion
+                    ),
+                    None => format!(
+                        "${} is not provided by sbuild or assigned in the script",
+                        reference.name
+                    ),
+                };
+                logger.warn(format!("{} -> {}", field.bold(), message));
+
+                let line_number = location.source_line(reference.line + 1);
+                if line_number != 0 {
+                    highlight_error_line(yaml_str, line_number, false, self.options.color, logger);
+                }
+                let file_line = if line_number != 0 && input_format != InputFormat::Json {
+                    line_number + 1
+                } else {
+                    line_number
+                };
+                self.record_diagnostic(
+                    file_path,
+                    "undefined-variable",
+                    &field,
+                    message,
+                    file_line,
+                    Severity::Warn,
+                );
+            }
+        }
+    }
+
+    // Warns when `x_exec.run` doesn't enable the required shell options, e.g.
+    // `set -e`, before its first command, so a failing step doesn't go on to
+    // package a broken build.
+    fn check_set_options(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        config: &BuildConfig,
+    ) {
+        let Some(required) = &self.options.set_options else {
+            return;
+        };
+        let missing = shell_options::missing(&config.x_exec.run, required);
+        if missing.is_empty() {
+            return;
+        }
+
+        let logger = &self.logger;
+        let field = "x_exec.run";
+        let message = format!(
+            "The script doesn't enable {} before its first command. Start it with `{}`",
+            missing.join(", "),
+            shell_options::set_command(&missing)
+        );
+        logger.warn(format!("{} -> {}", field.bold(), message));
+
+        let location = ScriptLocation::find(yaml_str, input_format == InputFormat::Json, "run");
+        let line_number = location.source_line(1);
+        if line_number != 0 {
+            highlight_error_line(yaml_str, line_number, false, self.options.color, logger);
+        }
+        let file_line = if line_number != 0 && input_format != InputFormat::Json {
+            line_number + 1
+        } else {
+            line_number
+        };
+        self.record_diagnostic(
+            file_path,
+            "missing-set-options",
+            field,
+            message,
+            file_line,
+            Severity::Warn,
+        );
+    }
+
+    // Runs shellcheck on the script of `x_exec.<key>`, reporting the findings
+    // at the line they refer to in the SBUILD. Fails if there are findings at
+    // error severity.
+    fn shellcheck(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        key: &str,
+        script: &str,
+    ) -> std::io::Result<()> {
+        let location = ScriptLocation::find(yaml_str, input_format == InputFormat::Json, key);
+
+        let fail_level = self.options.shellcheck_severity;
+        let outcome = self.run_shellcheck(script, fail_level)?;
+        self.report_shellcheck_findings(
+            file_path,
+            yaml_str,
+            input_format,
+            key,
+            &location,
+            outcome.findings,
+        );
+        if !outcome.success {
+            return Err(std::io::Error::other("Shellcheck emitted errors."));
         }
 
-        let _ = self.run_shellcheck(script, "warning");
+        // warnings are still shown when they don't fail the file, anything
+        // less severe only when asked for with the severity
+        if fail_level <= shellcheck::Level::Warning {
+            return Ok(());
+        }
+        if let Ok(outcome) = self.run_shellcheck(script, shellcheck::Level::Warning) {
+            self.report_shellcheck_findings(
+                file_path,
+                yaml_str,
+                input_format,
+                key,
+                &location,
+                outcome.findings,
+            );
+        }
 
         Ok(())
     }
 
+    // Reuses the outcome from the cache when the same script was already
+    // checked at the same severity.
+    fn run_shellcheck(
+        &self,
+        script: &str,
+        severity: shellcheck::Level,
+    ) -> std::io::Result<Outcome> {
+        let Some(ref cache) = self.cache else {
+            return shellcheck::run(script, severity);
+        };
+
+        let key = cache.key(&["shellcheck", severity.as_str(), script]);
+        if let Some(outcome) = cache.get(&key) {
+            if let Some(ref op_log) = self.op_log {
+                op_log.debug(format!("cache hit for shellcheck entry {}", key));
+            }
+            return Ok(outcome);
+        }
+        if let Some(ref op_log) = self.op_log {
+            op_log.debug(format!("cache miss for shellcheck entry {}", key));
+        }
+        let outcome = shellcheck::run(script, severity)?;
+        if let Err(err) = cache.put(&key, &outcome) {
+            self.logger.warn(format!(
+                "Failed to write shellcheck result to cache: {}",
+                err
+            ));
+        }
+        Ok(outcome)
+    }
+
+    fn report_shellcheck_findings(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        key: &str,
+        location: &ScriptLocation,
+        findings: Vec<Finding>,
+    ) {
+        let logger = &self.logger;
+        let field = format!("x_exec.{}", key);
+
+        for finding in findings {
+            let is_fatal = finding.level() >= self.options.shellcheck_severity;
+            let message = format!("SC{}: {}", finding.code, finding.message);
+            let msg = format!("{} -> {}", field.bold(), message);
+            if is_fatal {
+                logger.error(msg);
+            } else {
+                logger.warn(msg);
+            }
+
+            // route the findings through the logger so they respect the
+            // output mode and end up in the log file
+            let line_number = location.source_line(finding.line);
+            if line_number != 0 {
+                highlight_error_line(yaml_str, line_number, is_fatal, self.options.color, logger);
+            }
+
+            // the `#!/SBUILD` line is stripped from YAML sources, so shift the
+            // line back to the one in the file
+            let file_line = if line_number != 0 && input_format != InputFormat::Json {
+                line_number + 1
+            } else {
+                line_number
+            };
+            self.record_diagnostic(
+                file_path,
+                "shellcheck",
+                &field,
+                message,
+                file_line,
+                if is_fatal {
+                    Severity::Error
+                } else {
+                    Severity::Warn
+                },
+            );
+        }
+    }
+
     pub fn generate_pkgver(&self, config: &BuildConfig, pkgver_path: &str) -> bool {
         let logger = &self.logger;
         let x_exec = &config.x_exec;
-        let mut success = false;
 
-        match config.pkgver {
-            Some(ref pkgver) => {
-                logger.info("Using hard-coded pkgver");
-                let file = File::create(pkgver_path).unwrap();
-                let mut writer = BufWriter::new(file);
-                let _ = writer.write_all(pkgver.as_bytes());
+        if let Some(ref pkgver) = config.pkgver {
+            logger.info("Using hard-coded pkgver");
+            let file = File::create(pkgver_path).unwrap();
+            let mut writer = BufWriter::new(file);
+            let _ = writer.write_all(pkgver.as_bytes());
 
-                logger.success(&format!(
-                    "Version ({}) from pkgver written to {}",
-                    pkgver,
-                    pkgver_path.bright_cyan()
-                ));
-                success = true;
+            logger.success(format!(
+                "Version ({}) from pkgver written to {}",
+                pkgver,
+                pkgver_path.bright_cyan()
+            ));
+            return true;
+        }
+
+        let Some(ref pkgver) = x_exec.pkgver else {
+            // we don't care if the pkgver is not set
+            return true;
+        };
+
+        let Some(version) = self.run_pkgver(&x_exec.shell, pkgver) else {
+            return false;
+        };
+
+        let file = File::create(pkgver_path).unwrap();
+        let mut writer = BufWriter::new(file);
+        let _ = writer.write_all(version.as_bytes());
+
+        logger.success(format!(
+            "Fetched version ({}) using x_exec.pkgver written to {}",
+            &version,
+            pkgver_path.bright_cyan()
+        ));
+        true
+    }
+
+    // Runs the `x_exec.pkgver` script a second time and warns if it resolves
+    // to a different version than the one already written, which usually
+    // means the script tracks a moving target like `latest`.
+    fn is_pkgver_stable(&self, file_path: &str, config: &BuildConfig, pkgver_path: &str) -> bool {
+        let x_exec = &config.x_exec;
+        let (None, Some(ref pkgver)) = (&config.pkgver, &x_exec.pkgver) else {
+            return true;
+        };
+        let Ok(first) = std::fs::read_to_string(pkgver_path) else {
+            return true;
+        };
+
+        self.logger.info("Verifying pkgver");
+        let Some(second) = self.run_pkgver(&x_exec.shell, pkgver) else {
+            return false;
+        };
+
+        if first == second {
+            self.logger.success("pkgver is deterministic");
+            return true;
+        }
+
+        let message = format!(
+            "Produced different versions across runs ('{}' and '{}'). The source may not be pinned.",
+            first, second
+        );
+        self.logger
+            .warn(format!("{} -> {}", "x_exec.pkgver".bold(), message));
+        self.record_diagnostic(
+            file_path,
+            "unstable-pkgver",
+            "x_exec.pkgver",
+            message,
+            0,
+            Severity::Warn,
+        );
+        false
+    }
+
+    // Runs the `x_exec.pkgver` script and returns the version it printed,
+    // logging why if it didn't produce exactly one line of output.
+    fn run_pkgver(&self, shell: &str, pkgver: &str) -> Option<String> {
+        let logger = &self.logger;
+        let script = format!("#!/usr/bin/env {}\n{}", shell, pkgver);
+        let tmp = temp_script_file(&script);
+        let tmp_path = tmp.into_temp_path();
+        let mut version = None;
+
+        let (tx, rx) = sync::mpsc::channel();
+        thread::spawn({
+            let tmp_path = tmp_path.to_path_buf();
+            move || {
+                let cmd = Command::new(&tmp_path).output();
+                let _ = tx.send(cmd);
             }
-            None => {
-                if let Some(ref pkgver) = x_exec.pkgver {
-                    let script = format!("#!/usr/bin/env {}\n{}", x_exec.shell, pkgver);
-                    let tmp = temp_script_file(&script);
-                    let tmp_path = tmp.into_temp_path();
-
-                    let (tx, rx) = sync::mpsc::channel();
-                    thread::spawn({
-                        let tmp_path = tmp_path.to_path_buf();
-                        move || {
-                            let cmd = Command::new(&tmp_path).output();
-                            let _ = tx.send(cmd);
-                        }
-                    });
-
-                    match rx.recv_timeout(self.timeout) {
-                        Ok(cmd_result) => {
-                            match cmd_result {
-                                Ok(cmd) => {
-                                    if cmd.status.success() {
-                                        if !cmd.stderr.is_empty() {
-                                            logger.error("x.exec.pkgver script produced error.");
-                                            logger.error(String::from_utf8_lossy(&cmd.stderr));
-                                        } else {
-                                            let out = cmd.stdout;
-                                            let output_str = String::from_utf8_lossy(&out);
-                                            let output_str = output_str.trim();
-                                            if output_str.is_empty() {
-                                                logger.warn("x_exec.pkgver produced empty result. Skipping...");
-                                            } else if output_str.lines().count() > 1 {
-                                                logger.error(
-                                                    "x_exec.pkgver should only produce one output",
-                                                );
-                                                output_str.lines().for_each(|line| {
-                                                    logger.info(format!("-> {}", line.trim()));
-                                                });
-                                            } else {
-                                                let file = File::create(pkgver_path).unwrap();
-                                                let mut writer = BufWriter::new(file);
-                                                let _ = writer.write_all(output_str.as_bytes());
-
-                                                logger.success(format!("Fetched version ({}) using x_exec.pkgver written to {}", &output_str, pkgver_path.bright_cyan()));
-                                                success = true;
-                                            }
-                                        }
-                                    } else {
-                                        logger.error(format!("{} -> Failed to read output from pkgver script. Please make sure the script is valid.", "x_exec.pkgver".bold()));
-                                        if !cmd.stderr.is_empty() {
-                                            logger.error(String::from_utf8_lossy(&cmd.stderr));
-                                        }
-                                    }
-                                }
-                                Err(err) => {
-                                    logger.error(format!(
-                                        "{} -> pkgver script failed to execute. {}",
-                                        "x_exec.pkgver".bold(),
-                                        err
-                                    ));
-                                }
+        });
+
+        // a zero timeout waits for the script however long it takes
+        let cmd_result = if self.timeout.is_zero() {
+            rx.recv().ok()
+        } else {
+            rx.recv_timeout(self.timeout).ok()
+        };
+        match cmd_result {
+            Some(cmd_result) => match cmd_result {
+                Ok(cmd) => {
+                    if cmd.status.success() {
+                        if !cmd.stderr.is_empty() {
+                            logger.error("x.exec.pkgver script produced error.");
+                            logger.error(String::from_utf8_lossy(&cmd.stderr));
+                        } else {
+                            let out = cmd.stdout;
+                            let output_str = String::from_utf8_lossy(&out);
+                            let output_str = output_str.trim();
+                            if output_str.is_empty() {
+                                logger.warn("x_exec.pkgver produced empty result. Skipping...");
+                            } else if output_str.lines().count() > 1 {
+                                logger.error("x_exec.pkgver should only produce one output");
+                                output_str.lines().for_each(|line| {
+                                    logger.info(format!("-> {}", line.trim()));
+                                });
+                            } else {
+                                version = Some(output_str.to_string());
                             }
                         }
-                        Err(_) => {
-                            logger.error(format!(
-                                "{} -> pkgver script timed out after {:#?}",
-                                "x_exec.pkgver".bold(),
-                                self.timeout
-                            ));
+                    } else {
+                        logger.error(format!("{} -> Failed to read output from pkgver script. Please make sure the script is valid.", "x_exec.pkgver".bold()));
+                        if !cmd.stderr.is_empty() {
+                            logger.error(String::from_utf8_lossy(&cmd.stderr));
                         }
                     }
-
-                    let _ = tmp_path.close();
-                } else {
-                    // we don't care if the pkgver is not set
-                    success = true;
                 }
+                Err(err) => {
+                    logger.error(format!(
+                        "{} -> pkgver script failed to execute. {}",
+                        "x_exec.pkgver".bold(),
+                        err
+                    ));
+                }
+            },
+            None => {
+                logger.error(format!(
+                    "{} -> pkgver script timed out after {:#?}",
+                    "x_exec.pkgver".bold(),
+                    self.timeout
+                ));
             }
         }
+
+        let _ = tmp_path.close();
+        version
+    }
+
+    // unreachable sources fail the check, other URLs only produce warnings
+    fn check_urls(&self, file_path: &str, url_checker: &UrlChecker, config: &BuildConfig) -> bool {
+        let logger = &self.logger;
+        let mut success = true;
+
+        let urls = config
+            .src_url
+            .iter()
+            .map(|url| ("src_url", url, true))
+            .chain(
+                config
+                    .homepage
+                    .iter()
+                    .flatten()
+                    .map(|url| ("homepage", url, false)),
+            );
+
+        for (field, url, required) in urls {
+            let reason = match url_checker.check(url) {
+                Ok(response) if response.status.is_success() => {
+                    if field == "homepage" && self.options.homepage_content_type {
+                        self.check_homepage_content_type(file_path, url, &response);
+                    }
+                    continue;
+                }
+                Ok(response) => format!("returned {}", response.status),
+                Err(err) => err,
+            };
+            let message = format!("'{}' is not reachable: {}", url, reason);
+            let msg = format!("{} -> {}", field.bold(), message);
+            let severity = if required {
+                logger.error(msg);
+                success = false;
+                Severity::Error
+            } else {
+                logger.warn(msg);
+                Severity::Warn
+            };
+            self.record_diagnostic(file_path, "unreachable-url", field, message, 0, severity);
+        }
+
         success
     }
 
-    fn is_shellcheck_success(&self, config: &BuildConfig) -> bool {
+    // Warns about a homepage served as something else than a web page, e.g.
+    // a download. Lenient, since responses without a Content-Type are
+    // accepted.
+    fn check_homepage_content_type(&self, file_path: &str, url: &str, response: &UrlResponse) {
+        let Some(ref content_type) = response.content_type else {
+            return;
+        };
+        if HTML_CONTENT_TYPES.contains(&content_type.as_str()) {
+            return;
+        }
+        let message = format!(
+            "'{}' is served as {}, not as a web page. Point 'homepage' at the project's site",
+            url, content_type
+        );
+        self.logger
+            .warn(format!("{} -> {}", "homepage".bold(), message));
+        self.record_diagnostic(
+            file_path,
+            "non-html-homepage",
+            "homepage",
+            message,
+            0,
+            Severity::Warn,
+        );
+    }
+
+    fn is_shellcheck_success(
+        &self,
+        file_path: &str,
+        yaml_str: &str,
+        input_format: InputFormat,
+        config: &BuildConfig,
+    ) -> bool {
         let logger = &self.logger;
         let x_exec = &config.x_exec;
         let mut success = true;
 
         let script = format!("#!/usr/bin/env {}\n{}", x_exec.shell, x_exec.run);
-        if self.shellcheck(&script).is_err() {
+        if self
+            .shellcheck(file_path, yaml_str, input_format, "run", &script)
+            .is_err()
+        {
             logger.error(format!(
                 "{} -> Shellcheck verification failed.",
                 "x_exec.run".bold()
@@ -306,7 +1358,10 @@ impl Linter {
 
         if let Some(ref pkgver) = x_exec.pkgver {
             let script = format!("#!/usr/bin/env {}\n{}", x_exec.shell, pkgver);
-            if self.shellcheck(&script).is_err() {
+            if self
+                .shellcheck(file_path, yaml_str, input_format, "pkgver", &script)
+                .is_err()
+            {
                 logger.error(format!(
                     "{} -> Shellcheck verification failed.",
                     "x_exec.pkgver".bold()
@@ -322,6 +1377,7 @@ impl Linter {
 enum FileError {
     InvalidFile(String),
     NotFound(String),
+    Unreadable(String, io::Error),
 }
 
 impl Display for FileError {
@@ -331,10 +1387,125 @@ impl Display for FileError {
                 writeln!(f, "Invalid file {}. Please provide a valid YAML file.", fp)
             }
             FileError::NotFound(fp) => writeln!(f, "File {} not found.", fp),
+            FileError::Unreadable(fp, err) => writeln!(f, "Failed to read {}: {}", fp, err),
         }
     }
 }
 
+/// Lints SBUILD content held in memory with the default options and returns
+/// every diagnostic, warnings included, instead of printing them.
+///
+/// These functions are `Linter::lint_str` and `Linter::parse` on a linter
+/// of their own that logs nowhere, for callers that don't have a logger or
+/// a report: the Linter methods log as configured and add to the linter's
+/// report, these only return. Like `lint_str`, only the checks that depend
+/// on the content run; shellcheck, URL checks and the pkgver script don't.
+/// The diagnostics are reported under `IN_MEMORY_PATH`.
+///
+/// ```
+/// let errors = sbuild_linter::lint_string("#!/SBUILD\npkg: hello\n");
+/// assert!(errors
+///     .iter()
+///     .any(|e| e.rule == "missing-field" && e.field == "description"));
+/// ```
+pub fn lint_string(content: &str) -> Vec<ErrorDetails> {
+    lint_string_with(content, LintOptions::default())
+}
+
+/// Same as `lint_string` with the given options, e.g. to enable the optional
+/// checks.
+///
+/// ```
+/// use sbuild_linter::{lint_string_with, LintOptions};
+///
+/// let options = LintOptions {
+///     strict_fields: true,
+///     ..LintOptions::default()
+/// };
+/// let errors = lint_string_with("#!/SBUILD\npkg: hello\nx_custom: 1\n", options);
+/// assert!(errors.iter().any(|e| e.field == "x_custom"));
+/// ```
+pub fn lint_string_with(content: &str, options: LintOptions) -> Vec<ErrorDetails> {
+    silent_linter(options).lint_collect(content).1
+}
+
+/// Validates SBUILD content like `lint_string_with` and returns the typed
+/// config when it passes, or the diagnostics when it doesn't, see
+/// `Linter::parse`. The warnings of content that passes are dropped.
+///
+/// ```
+/// use sbuild_linter::{parse_string, LintOptions};
+///
+/// let content = "#!/SBUILD
+/// _disabled: false
+/// pkg: hello
+/// description: Prints a greeting
+/// src_url:
+///   - https://example.com/hello
+/// x_exec:
+///   shell: sh
+///   run: echo hello
+/// ";
+/// let config = parse_string(content, LintOptions::default()).unwrap();
+/// assert_eq!(config.pkg, "hello");
+/// assert!(parse_string("#!/SBUILD\npkg: hello\n", LintOptions::default()).is_err());
+/// ```
+pub fn parse_string(content: &str, options: LintOptions) -> Result<BuildConfig, Vec<ErrorDetails>> {
+    silent_linter(options).parse(content)
+}
+
+fn silent_linter(options: LintOptions) -> Linter {
+    // the receiver is dropped right away, which discards the log
+    let logger = LogManager::new(sync::mpsc::channel().0).create_logger(None::<&str>);
+    Linter::new(logger, Duration::ZERO).with_options(options)
+}
+
+// Maps the input path to a relative one so it can be mirrored under the
+// output directory, e.g. both `./pkgs/a.yaml` and `$PWD/pkgs/a.yaml` become
+// `pkgs/a.yaml`.
+pub fn mirrored_path(file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    let current_dir = env::current_dir();
+    let path = current_dir
+        .as_ref()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+
+    path.components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect()
+}
+
+// Maps every top-level key of the YAML source to the lines it appears on, so
+// looking up the keys of a file scans it once. Keys are the unindented `key:`
+// lines, quoted or not, which doesn't match the key name inside values.
+fn index_top_level_keys(yaml_str: &str) -> HashMap<String, Vec<usize>> {
+    let mut lines = HashMap::new();
+    for (index, line) in yaml_str.lines().enumerate() {
+        if line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        let Some((key, _)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim_end();
+        let key = key
+            .strip_prefix('"')
+            .and_then(|key| key.strip_suffix('"'))
+            .or_else(|| {
+                key.strip_prefix('\'')
+                    .and_then(|key| key.strip_suffix('\''))
+            })
+            .unwrap_or(key);
+        lines
+            .entry(key.to_string())
+            .or_insert_with(Vec::new)
+            .push(index + 1);
+    }
+    lines
+}
+
 fn get_line_number_for_key(yaml_str: &str, key: &str) -> usize {
     let mut line_number = 0;
     for (index, line) in yaml_str.lines().enumerate() {
@@ -346,6 +1517,20 @@ fn get_line_number_for_key(yaml_str: &str, key: &str) -> usize {
     line_number
 }
 
+// JSON keys are always quoted, so look for `"key":` to avoid matching the
+// key name inside values.
+fn get_line_number_for_json_key(json_str: &str, key: &str) -> usize {
+    let needle = format!("\"{}\"", key);
+    for (index, line) in json_str.lines().enumerate() {
+        if let Some(pos) = line.find(&needle) {
+            if line[pos + needle.len()..].trim_start().starts_with(':') {
+                return index + 1;
+            }
+        }
+    }
+    0
+}
+
 fn get_pkg_id(src: &str) -> String {
     let (_, url) = src.split_once("://").unwrap();
     let (url, _) = url.split_once('?').unwrap_or((url, ""));
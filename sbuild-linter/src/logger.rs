@@ -7,14 +7,45 @@ use std::{
 };
 
 pub enum LogMessage {
+    // the line naming the file a log is for
+    Heading(String),
     Info(String),
     Error(String),
     Warn(String),
     Success(String),
     CustomError(String),
+    // a serialized file result, printed even when the log isn't shown
+    Result(String),
+    // a debugging dump, printed to stderr even when the log isn't shown
+    Dump(String),
     Done,
 }
 
+// How much of the log of each file is shown: errors only, then warnings and
+// successes, then the steps taken, and finally the operations of the linter
+// itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    Debug,
+}
+
+impl Verbosity {
+    pub fn shows(&self, log: &LogMessage) -> bool {
+        match log {
+            LogMessage::Error(_)
+            | LogMessage::CustomError(_)
+            | LogMessage::Result(_)
+            | LogMessage::Dump(_)
+            | LogMessage::Done => true,
+            LogMessage::Warn(_) | LogMessage::Success(_) => *self >= Verbosity::Normal,
+            LogMessage::Heading(_) | LogMessage::Info(_) => *self >= Verbosity::Verbose,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct LogManager {
     sender: Sender<LogMessage>,
@@ -81,7 +112,7 @@ impl TaskLogger {
                     let timestamp = format!("[{:02}:{:02}.{:03}]", minutes, seconds, milliseconds);
 
                     let line = if line.is_empty() {
-                        format!("{}", timestamp)
+                        timestamp.to_string()
                     } else {
                         format!("{}➜ {}", timestamp, line)
                     };
@@ -93,12 +124,9 @@ impl TaskLogger {
 
     pub fn move_log_file<P: AsRef<Path>>(&self, new_path: P) -> std::io::Result<()> {
         if let Some(file) = &self.file {
-            let mut file_guard = file.lock().map_err(|_| {
-                std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to acquire lock on log file",
-                )
-            })?;
+            let mut file_guard = file
+                .lock()
+                .map_err(|_| std::io::Error::other("Failed to acquire lock on log file"))?;
 
             file_guard.file.flush()?;
             let old_path = file_guard.path.clone();
@@ -120,6 +148,12 @@ impl TaskLogger {
         Ok(())
     }
 
+    pub fn heading(&self, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.write_to_file(&msg);
+        let _ = self.sender.send(LogMessage::Heading(msg.to_string()));
+    }
+
     pub fn info(&self, msg: impl Into<String>) {
         let msg = msg.into();
         self.write_to_file(&msg);
@@ -144,6 +178,14 @@ impl TaskLogger {
         let _ = self.sender.send(LogMessage::Success(msg.to_string()));
     }
 
+    pub fn result(&self, line: impl Into<String>) {
+        let _ = self.sender.send(LogMessage::Result(line.into()));
+    }
+
+    pub fn dump(&self, dump: impl Into<String>) {
+        let _ = self.sender.send(LogMessage::Dump(dump.into()));
+    }
+
     pub fn custom_error(&self, msg: impl Into<String>) {
         let msg = msg.into();
         self.write_to_file(&msg);
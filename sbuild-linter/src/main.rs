@@ -1,145 +1,669 @@
 use std::{
-    collections::HashSet,
+    collections::HashMap,
     env,
-    fs::OpenOptions,
-    io::Write,
-    path::PathBuf,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, IsTerminal, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    process,
     sync::{
         self,
         atomic::{AtomicUsize, Ordering},
-        Arc, LazyLock,
+        mpsc::{Receiver, Sender},
+        Arc, LazyLock, Mutex,
     },
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+use clap::{CommandFactory, Parser};
+use cli::{Cli, ColorChoice, Command, ConcurrencyModel, LintArgs};
 use colored::Colorize;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use indexmap::IndexSet;
 use sbuild_linter::{
-    logger::{LogManager, LogMessage},
-    semaphore::Semaphore,
-    Linter,
+    cache::Cache,
+    checkpoint::Checkpoint,
+    diff::diff_results,
+    discover::{expand_paths, read_path_list},
+    duplicates::DuplicateConfigs,
+    error::{ErrorDetails, FailOn, Severity},
+    logger::{LogManager, LogMessage, Verbosity},
+    mirrored_path,
+    oplog::{OpLevel, OpLog},
+    profile::Profile,
+    report::{OutputFormat, Report, Results, RunReport},
+    rules,
+    shapes::FieldShapes,
+    unique::UniqueIds,
+    url_check::UrlChecker,
+    validator, LintOptions, Linter, DEFAULT_FIELD_ORDER, VALID_PKG_TYPES,
 };
 
+mod cli;
+mod priority;
+
+// jobs mostly wait on shellcheck and pkgver scripts, so running a few per
+// CPU is fine, but far more than that only adds overhead
+const MAX_JOBS_PER_CPU: usize = 8;
+
 static CHECK_MARK: LazyLock<colored::ColoredString> = LazyLock::new(|| "✔".bright_green().bold());
 static CROSS_MARK: LazyLock<colored::ColoredString> = LazyLock::new(|| "〤".bright_red().bold());
 static WARN: LazyLock<colored::ColoredString> = LazyLock::new(|| "⚠️".bright_yellow().bold());
 
-fn usage() -> String {
-    r#"Usage: sbuild-linter [OPTIONS] [FILES]
+fn main() {
+    let cli = Cli::parse();
 
-A linter for SBUILD package files.
+    // `auto` is left to `colored`, which looks at NO_COLOR, CLICOLOR,
+    // CLICOLOR_FORCE and whether stdout is a terminal
+    match cli.color {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => {}
+    }
 
-Options:
-   --pkgver, -p          Enable pkgver mode
-   --no-shellcheck       Disable shellcheck
-   --parallel <N>        Run N jobs in parallel (default: 4)
-   --inplace, -i         Replace the original file on success
-   --success <PATH>      File to store successful packages list
-   --fail <PATH>         File to store failed packages list
-   --timeout <DURATION>  Timeout duration after which the pkgver check exits
-   --help, -h            Show this help message
+    match cli.command {
+        Some(Command::Lint(args)) => lint(*args, cli.color),
+        Some(Command::Explain { rule }) => explain(&rule),
+        Some(Command::ListRules) => list_rules(),
+        Some(Command::ListCategories { json }) => list_categories(json),
+        Some(Command::DumpSchema) => dump_schema(),
+        Some(Command::Diff { old, new }) => diff(&old, &new),
+        Some(Command::Completions { shell }) => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+        }
+        None => lint(cli.lint, cli.color),
+    }
+}
 
-Arguments:
-   FILE...               One or more package files to validate"#
-        .to_string()
+fn print_log(log: LogMessage) {
+    match log {
+        LogMessage::Heading(msg) | LogMessage::Info(msg) => {
+            println!("{}", msg);
+        }
+        LogMessage::Error(msg) => {
+            eprintln!("[{}] {}", &*CROSS_MARK, msg);
+        }
+        LogMessage::Warn(msg) => {
+            eprintln!("[{}] {}", &*WARN, msg);
+        }
+        LogMessage::Success(msg) => {
+            println!("[{}] {}", &*CHECK_MARK, msg);
+        }
+        LogMessage::CustomError(msg) => {
+            eprintln!("{}", msg);
+        }
+        LogMessage::Result(line) => {
+            println!("{}", line);
+        }
+        LogMessage::Dump(dump) => {
+            eprintln!("{}", dump);
+        }
+        LogMessage::Done => {}
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-
-    let mut pkgver = false;
-    let mut disable_shellcheck = false;
-    let mut files: HashSet<String> = HashSet::new();
-    let mut parallel = None;
-    let mut inplace = false;
-    let mut success_path = None;
-    let mut fail_path = None;
-    let mut timeout = 30;
-
-    let mut iter = args.iter().skip(1);
-    while let Some(arg) = iter.next() {
-        match arg.as_str() {
-            "--pkgver" | "-p" => {
-                pkgver = true;
-            }
-            "--inplace" | "-i" => {
-                inplace = true;
-            }
-            "--no-shellcheck" => {
-                disable_shellcheck = true;
-            }
-            "--success" => {
-                if let Some(next) = iter.next() {
-                    if next.starts_with("-") {
-                        eprintln!("Expected file path. Got flag instead.");
-                        std::process::exit(1);
-                    }
-                    success_path = Some(next);
-                } else {
-                    eprintln!("Success file path is not provided.");
-                    eprintln!("{}", usage());
-                    std::process::exit(1);
-                }
+fn explain(id: &str) {
+    let Some(rule) = rules::find_rule(id) else {
+        eprintln!(
+            "Unknown rule '{}'. Run `sbuild-linter list-rules` for the list.",
+            id
+        );
+        std::process::exit(1);
+    };
+
+    println!("{} ({})", rule.id.bold(), rule.severity.as_str());
+    println!();
+    println!("{}", rule.summary);
+    println!();
+    println!("{}", rule.explanation);
+}
+
+fn list_rules() {
+    let width = rules::RULES
+        .iter()
+        .map(|rule| rule.id.len())
+        .max()
+        .unwrap_or(0);
+    for rule in rules::RULES {
+        println!(
+            "{:width$}  {:5}  {}",
+            rule.id,
+            rule.severity.as_str(),
+            rule.summary,
+            width = width
+        );
+    }
+}
+
+fn list_categories(json: bool) {
+    if json {
+        let categories: Vec<_> = validator::categories().collect();
+        println!("{}", serde_json::json!(categories));
+        return;
+    }
+    for category in validator::categories() {
+        println!("{}", category);
+    }
+}
+
+fn dump_schema() {
+    match serde_json::to_string_pretty(&validator::schema()) {
+        Ok(schema) => println!("{}", schema),
+        Err(err) => {
+            eprintln!("Failed to serialize schema: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn read_results(path: &Path) -> Results {
+    match File::open(path).map(|file| Results::from_reader(BufReader::new(file))) {
+        Ok(Ok(results)) => results,
+        Ok(Err(err)) => {
+            eprintln!("Failed to parse {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn diff(old: &Path, new: &Path) {
+    let file_diffs = diff_results(&read_results(old), &read_results(new));
+
+    let (mut new_count, mut fixed_count, mut unchanged_count) = (0, 0, 0);
+    for file_diff in &file_diffs {
+        if file_diff.new.is_empty() && file_diff.fixed.is_empty() && file_diff.unchanged.is_empty()
+        {
+            continue;
+        }
+
+        println!("{}", file_diff.path.bold());
+        let groups = [
+            ("+".bright_red().bold(), &file_diff.new),
+            ("-".bright_green().bold(), &file_diff.fixed),
+            ("=".dimmed(), &file_diff.unchanged),
+        ];
+        for (marker, diagnostics) in groups {
+            for diagnostic in diagnostics {
+                println!(
+                    "  {} {} [{}] {} -> {}",
+                    marker,
+                    diagnostic.severity.as_str(),
+                    diagnostic.rule,
+                    diagnostic.field,
+                    diagnostic.message
+                );
             }
-            "--fail" => {
-                if let Some(next) = iter.next() {
-                    if next.starts_with("-") {
-                        eprintln!("Expected file path. Got flag instead.");
-                        std::process::exit(1);
-                    }
-                    fail_path = Some(next);
-                } else {
-                    eprintln!("Fail file path is not provided.");
-                    eprintln!("{}", usage());
-                    std::process::exit(1);
-                }
+        }
+        println!();
+
+        new_count += file_diff.new.len();
+        fixed_count += file_diff.fixed.len();
+        unchanged_count += file_diff.unchanged.len();
+    }
+
+    println!(
+        "[{}] {} new, {} fixed, {} unchanged",
+        "+".bright_blue().bold(),
+        new_count,
+        fixed_count,
+        unchanged_count
+    );
+}
+
+// Parses `pkg_type:Category` pairs, skipping empty entries so an empty list
+// disables the check.
+fn parse_category_pairs(list: Vec<String>) -> Result<Vec<(String, String)>, String> {
+    list.iter()
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (pkg_type, category) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("Expected pkg_type:Category, got '{}'", entry))?;
+            let pkg_type = pkg_type.trim();
+            if !VALID_PKG_TYPES.contains(&pkg_type) {
+                return Err(format!(
+                    "Unknown pkg_type in --incoherent-categories: {}",
+                    pkg_type
+                ));
             }
-            "--parallel" => {
-                if let Some(next) = iter.next() {
-                    match next.parse::<usize>() {
-                        Ok(count) => parallel = Some(count),
-                        Err(_) => {
-                            eprintln!("Invalid number of parallel jobs: '{}'", next);
-                            eprintln!("{}", usage());
-                            std::process::exit(1);
-                        }
-                    }
-                } else {
-                    eprintln!("Number of parallel jobs not provided. Setting 4.");
-                    parallel = Some(4);
-                }
+            let category = validator::canonical_category(category.trim()).ok_or_else(|| {
+                format!("Unknown category in --incoherent-categories: {}", category)
+            })?;
+            Ok((pkg_type.to_string(), category.to_string()))
+        })
+        .collect()
+}
+
+// `*` doesn't match `/`, so patterns behave like in gitignore files, e.g.
+// `pkgs/*.yaml` doesn't match `pkgs/sub/a.yaml` but `pkgs/**` does.
+fn build_globset(patterns: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    builder.build()
+}
+
+fn shellcheck_version() -> Option<String> {
+    let output = process::Command::new("shellcheck")
+        .arg("--version")
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("version:"))
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "(unknown version)".to_string());
+    Some(version)
+}
+
+// Path of a store file, resolved through links, so two spellings of the same
+// file compare equal. Files that don't exist yet are resolved through their
+// directory.
+fn store_identity(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    fs::canonicalize(path)
+        .or_else(|_| {
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let file_name = path.file_name().unwrap_or_default();
+            fs::canonicalize(parent).map(|parent| parent.join(file_name))
+        })
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+// Opens the file listing the passed or failed files. Paths are appended to
+// what earlier runs left unless `truncate` is set, which `warn` points out.
+fn open_store(path: &str, flag: &str, truncate: bool, warn: bool) -> File {
+    let mut open_options = OpenOptions::new();
+    open_options.create(true);
+    if truncate {
+        open_options.write(true).truncate(true);
+    } else {
+        open_options.append(true);
+    }
+    match open_options.open(path) {
+        Ok(file) => {
+            if warn && file.metadata().is_ok_and(|metadata| metadata.len() > 0) {
+                eprintln!(
+                    "[{}] {} {} isn't empty, the paths are appended. Use --truncate-stores to start over",
+                    &*WARN, flag, path
+                );
             }
-            "--timeout" => {
-                if let Some(next) = iter.next() {
-                    match next.parse::<usize>() {
-                        Ok(duration) => timeout = duration,
-                        Err(_) => {
-                            eprintln!("Invalid duration: '{}'", next);
-                            eprintln!("{}", usage());
-                            std::process::exit(1);
-                        }
-                    };
-                }
+            file
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn hostname() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+fn is_network_available(timeout: Duration) -> bool {
+    ("github.com", 443)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .is_some_and(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok())
+}
+
+// Prints the state of everything a run with the given flags depends on.
+// Returns false if a required dependency is missing.
+fn check_env(args: &LintArgs) -> bool {
+    let mut ok = true;
+    let mut check = |passed: bool, required: bool, msg: String| {
+        let mark = if passed {
+            &*CHECK_MARK
+        } else if required {
+            &*CROSS_MARK
+        } else {
+            &*WARN
+        };
+        println!("[{}] {}", mark, msg);
+        ok &= passed || !required;
+    };
+
+    match shellcheck_version() {
+        Some(version) => check(true, true, format!("shellcheck {}", version)),
+        None if args.no_shellcheck || args.structure_only => check(
+            false,
+            false,
+            "shellcheck not found, but not needed with --no-shellcheck or --structure-only"
+                .to_string(),
+        ),
+        None => check(
+            false,
+            true,
+            "shellcheck not found. Please install.".to_string(),
+        ),
+    }
+
+    let cpus = thread::available_parallelism().map_or(1, |cpus| cpus.get());
+    let jobs = args.jobs();
+    check(
+        jobs <= cpus,
+        false,
+        format!("{} CPU(s) available for {} job(s)", cpus, jobs),
+    );
+
+    match tempfile::tempfile() {
+        Ok(_) => check(
+            true,
+            true,
+            format!(
+                "Temporary directory {} is writable",
+                env::temp_dir().display()
+            ),
+        ),
+        Err(err) => check(
+            false,
+            true,
+            format!(
+                "Temporary directory {} is not writable: {}",
+                env::temp_dir().display(),
+                err
+            ),
+        ),
+    }
+
+    if !args.inplace {
+        // the output directory is created on demand, so check the closest
+        // directory that exists
+        let out_dir = args.out_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        let existing = out_dir
+            .ancestors()
+            .find(|dir| dir.is_dir())
+            .unwrap_or(Path::new("."));
+        match tempfile::tempfile_in(existing) {
+            Ok(_) => check(
+                true,
+                true,
+                format!("Output directory {} is writable", out_dir.display()),
+            ),
+            Err(err) => check(
+                false,
+                true,
+                format!(
+                    "Output directory {} is not writable: {}",
+                    out_dir.display(),
+                    err
+                ),
+            ),
+        }
+    }
+
+    if let Some(ref cache_dir) = args.cache_dir {
+        let writable = fs::create_dir_all(cache_dir)
+            .and_then(|_| tempfile::tempfile_in(cache_dir))
+            .map(|_| ());
+        match writable {
+            Ok(()) => check(
+                true,
+                true,
+                format!("Cache directory {} is writable", cache_dir.display()),
+            ),
+            Err(err) => check(
+                false,
+                true,
+                format!(
+                    "Cache directory {} is not writable: {}",
+                    cache_dir.display(),
+                    err
+                ),
+            ),
+        }
+    }
+
+    if args.check_urls || args.pkgver {
+        // URL checks can't work offline, while pkgver scripts may not need
+        // the network at all
+        let timeout = if args.timeout.is_zero() {
+            Duration::from_secs(30)
+        } else {
+            args.timeout
+        };
+        let available = is_network_available(timeout);
+        check(
+            available,
+            args.check_urls,
+            if available {
+                "Network is available".to_string()
+            } else {
+                "Network is unavailable".to_string()
+            },
+        );
+    }
+
+    ok
+}
+
+fn lint(args: LintArgs, color: ColorChoice) {
+    // before any thread is spawned, so the workers inherit them, and before
+    // the default number of workers is taken from the CPUs available
+    if let Some(nice) = args.nice {
+        if let Err(err) = priority::set_nice(nice) {
+            eprintln!(
+                "[{}] Failed to set the nice level to {}: {}",
+                &*WARN, nice, err
+            );
+        }
+    }
+    if let Some(ref cpus) = args.cpu_affinity {
+        if let Err(err) = priority::set_cpu_affinity(cpus) {
+            eprintln!("[{}] Failed to set the CPU affinity: {}", &*WARN, err);
+        }
+    }
+
+    if args.check_env {
+        let ok = check_env(&args);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    let jobs = args.jobs();
+    let verbosity = args.verbosity();
+    let LintArgs {
+        pkgver,
+        pkgver_verify: verify_pkgver,
+        no_shellcheck,
+        structure_only,
+        shellcheck_severity,
+        parallel,
+        no_parallel: _,
+        concurrency_model,
+        nice: _,
+        cpu_affinity: _,
+        repeat,
+        ordered,
+        live_log,
+        inplace,
+        out_dir,
+        stdout: to_stdout,
+        success: success_path,
+        fail: fail_path,
+        truncate_stores,
+        timeout,
+        profile: profile_path,
+        log_file,
+        log_level,
+        cache_dir,
+        input_format,
+        check_urls,
+        url_jobs,
+        url_host_jobs,
+        url_delay,
+        strict_fields,
+        strict_description,
+        max_fields,
+        max_categories,
+        max_size,
+        check_unique,
+        check_field_shapes,
+        check_duplicates,
+        check_env: _,
+        fix,
+        primary_categories,
+        require_git_pin,
+        check_icon_host,
+        check_arch_sources,
+        check_homepage_type,
+        require_set,
+        placeholders,
+        incoherent_categories,
+        gui_types,
+        max_line_length,
+        line_length_exempt,
+        field_order,
+        vcs_blame,
+        allow_var,
+        max_depth,
+        no_recurse,
+        exclude,
+        count: count_only,
+        no_summary,
+        dump_ast: dump_config,
+        quiet: _,
+        verbose: _,
+        format,
+        group_by,
+        fail_on,
+        report_dir,
+        report: run_report_path,
+        resume,
+        input_list,
+        mut files,
+    } = args;
+    // duplicates are dropped while keeping the input order, so runs over the
+    // same arguments produce the same output
+    let exclude = match build_globset(&exclude) {
+        Ok(exclude) => exclude,
+        Err(err) => {
+            eprintln!("[{}] {}", &*CROSS_MARK, err);
+            std::process::exit(1);
+        }
+    };
+    let op_log = match log_file {
+        Some(ref log_file) => match OpLog::create(log_file, log_level) {
+            Ok(op_log) => Some(Arc::new(op_log)),
+            Err(err) => {
+                eprintln!(
+                    "[{}] Failed to create log file {}: {}",
+                    &*CROSS_MARK,
+                    log_file.display(),
+                    err
+                );
+                std::process::exit(1);
             }
-            "--help" | "-h" => {
-                println!("{}", usage());
-                return;
+        },
+        // shown along with the diagnostics instead
+        None if verbosity == Verbosity::Debug => Some(Arc::new(OpLog::stderr(OpLevel::Debug))),
+        None => None,
+    };
+    let checkpoint = match resume {
+        Some(ref resume) => match Checkpoint::open(resume) {
+            Ok(checkpoint) => Some(Arc::new(checkpoint)),
+            Err(err) => {
+                eprintln!(
+                    "[{}] Failed to open checkpoint {}: {}",
+                    &*CROSS_MARK,
+                    resume.display(),
+                    err
+                );
+                std::process::exit(1);
             }
-            arg => {
-                if arg.starts_with("--") {
-                    eprintln!("Unknown argument '{}'", arg);
-                    eprintln!("{}", usage());
-                    std::process::exit(1);
-                } else {
-                    files.insert(arg.to_string());
-                }
+        },
+        None => None,
+    };
+    if let Some(ref input_list) = input_list {
+        let listed = if input_list.as_os_str() == "-" {
+            read_path_list(io::stdin().lock())
+        } else {
+            File::open(input_list).and_then(|file| read_path_list(BufReader::new(file)))
+        };
+        match listed {
+            Ok(listed) => files.extend(listed),
+            Err(err) => {
+                eprintln!(
+                    "[{}] Failed to read input list {}: {}",
+                    &*CROSS_MARK,
+                    input_list.display(),
+                    err
+                );
+                std::process::exit(1);
             }
         }
     }
+    let max_depth = if no_recurse { Some(0) } else { max_depth };
+    let files: IndexSet<String> = expand_paths(files, max_depth)
+        .into_iter()
+        .filter(|file_path| {
+            let excluded = exclude.is_match(mirrored_path(file_path));
+            if let (true, Some(ref op_log)) = (excluded, &op_log) {
+                op_log.info(format!("{}: skipped, matches --exclude", file_path));
+            }
+            !excluded
+        })
+        .collect();
+    let incoherent_categories = match incoherent_categories.map(parse_category_pairs).transpose() {
+        Ok(pairs) => pairs,
+        Err(err) => {
+            eprintln!("[{}] {}", &*CROSS_MARK, err);
+            std::process::exit(1);
+        }
+    };
+    let mut options = LintOptions {
+        strict_fields,
+        strict_description,
+        max_fields,
+        max_categories,
+        max_size,
+        verify_pkgver,
+        fix,
+        primary_categories,
+        vcs_blame,
+        allowed_vars: allow_var,
+        field_order,
+        git_pin_keys: require_git_pin,
+        icon_hosts: check_icon_host,
+        homepage_content_type: check_homepage_type,
+        arch_sources: check_arch_sources,
+        set_options: require_set,
+        placeholders,
+        shellcheck_severity,
+        incoherent_categories,
+        gui_types,
+        max_line_length,
+        line_length_exempt,
+        color: true,
+    };
 
-    if files.is_empty() {
-        eprintln!("{}", usage());
-        std::process::exit(1);
+    // clap rejects --pkgver and --check-urls with --structure-only, leaving
+    // shellcheck as the only slow check to skip
+    let disable_shellcheck = no_shellcheck || structure_only;
+
+    let cpus = thread::available_parallelism().map_or(1, |cpus| cpus.get());
+    if jobs > cpus * MAX_JOBS_PER_CPU {
+        eprintln!(
+            "[{}] --parallel {} is far more than the {} available CPU(s)",
+            &*WARN, jobs, cpus
+        );
     }
 
     if !disable_shellcheck && which::which("shellcheck").is_err() {
@@ -147,20 +671,149 @@ fn main() {
         std::process::exit(1);
     }
 
-    println!("sbuild-linter v{}", env!("CARGO_PKG_VERSION"));
+    if let Some(ref field_order) = options.field_order {
+        let fields: Vec<&str> = DEFAULT_FIELD_ORDER.split(',').collect();
+        if let Some(field) = field_order
+            .iter()
+            .find(|field| !fields.contains(&field.as_str()))
+        {
+            eprintln!(
+                "[{}] Unknown field in --field-order: {}",
+                &*CROSS_MARK, field
+            );
+            std::process::exit(1);
+        }
+    }
+
+    if to_stdout && format != OutputFormat::Text {
+        eprintln!(
+            "[{}] --stdout can't be combined with a machine-readable --format",
+            &*CROSS_MARK
+        );
+        std::process::exit(1);
+    }
+
+    if let (Some(success_path), Some(fail_path)) = (&success_path, &fail_path) {
+        if store_identity(success_path) == store_identity(fail_path) {
+            eprintln!(
+                "[{}] --success and --fail both point to {}, which would mix the lists",
+                &*CROSS_MARK, fail_path
+            );
+            std::process::exit(1);
+        }
+    }
+
+    // machine-readable formats own stdout, so the human-readable output is
+    // suppressed the same way as in count mode
+    let quiet = count_only || format != OutputFormat::Text || to_stdout;
+    // stdout then carries data, so the human-readable output left on stderr
+    // is colored if stderr is a terminal. The data itself is never colored.
+    if color == ColorChoice::Auto
+        && (format != OutputFormat::Text || to_stdout)
+        && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && env::var_os("CLICOLOR_FORCE").is_none_or(|value| value == "0")
+    {
+        colored::control::set_override(io::stderr().is_terminal());
+    }
+    options.color = colored::control::SHOULD_COLORIZE.should_colorize();
+
+    if !quiet && verbosity > Verbosity::Quiet {
+        println!("sbuild-linter v{}", env!("CARGO_PKG_VERSION"));
+    }
 
     let now = Instant::now();
+    let started = SystemTime::now();
     let success = Arc::new(AtomicUsize::new(0));
     let fail = Arc::new(AtomicUsize::new(0));
 
-    let (tx, rx) = sync::mpsc::channel();
-    let log_manager = LogManager::new(tx.clone());
+    let (tx, rx) = sync::mpsc::channel::<Receiver<LogMessage>>();
+
+    // a resumed run continues the lists of the earlier one
+    let warn_appending = !truncate_stores && resume.is_none();
+    let fail_store = fail_path.map(|fail_path| {
+        Arc::new(open_store(
+            &fail_path,
+            "--fail",
+            truncate_stores,
+            warn_appending,
+        ))
+    });
+    let success_store = success_path.map(|success_path| {
+        Arc::new(open_store(
+            &success_path,
+            "--success",
+            truncate_stores,
+            warn_appending,
+        ))
+    });
+
+    // each file logs to its own channel, drained in input order so the output
+    // of parallel jobs doesn't interleave. With `live_log` the channel of a
+    // file is only handed over once it completes, so the logs come in the
+    // order the files complete instead. Otherwise the log isn't shown, so
+    // every file shares one channel and results are streamed as the files
+    // complete.
+    let in_order = parallel.is_none() || ordered;
+    let completed_tx = (!in_order && live_log).then(|| tx.clone());
+    let show_log = in_order || live_log;
+    let shared_tx = if show_log {
+        None
+    } else {
+        let (shared_tx, shared_rx) = sync::mpsc::channel();
+        let _ = tx.send(shared_rx);
+        Some(shared_tx)
+    };
 
-    let fail_store = if let Some(fail_path) = fail_path {
-        match OpenOptions::new().create(true).append(true).open(fail_path) {
-            Ok(f) => Some(Arc::new(f)),
+    let logger_handle = thread::spawn(move || {
+        // with --stdout the validated YAML owns stdout, but the diagnostics
+        // are still shown on stderr
+        let show_diagnostics = show_log && (!quiet || to_stdout);
+        let show_log = show_log && !quiet;
+        for file_rx in rx {
+            // below -v the heading naming the file is only shown along with
+            // the first other line of its log
+            let mut heading = None;
+            // the highlighted source lines go with the diagnostic before them,
+            // so they are hidden along with a hidden warning
+            let mut context_shown = true;
+            for log in file_rx {
+                let show = match log {
+                    LogMessage::Result(_) | LogMessage::Dump(_) => true,
+                    LogMessage::Error(_) | LogMessage::Warn(_) => {
+                        context_shown = show_diagnostics && verbosity.shows(&log);
+                        context_shown
+                    }
+                    LogMessage::CustomError(_) => show_diagnostics && context_shown,
+                    _ => show_log && verbosity.shows(&log),
+                };
+                if !show {
+                    if let LogMessage::Heading(_) = log {
+                        heading = Some(log);
+                    }
+                    continue;
+                }
+                if show_log && !matches!(log, LogMessage::Result(_) | LogMessage::Dump(_)) {
+                    if let Some(heading) = heading.take() {
+                        print_log(heading);
+                    }
+                }
+                print_log(log);
+            }
+        }
+    });
+
+    let profile = profile_path.as_ref().map(|_| Arc::new(Profile::new()));
+
+    let url_checker = if check_urls {
+        match UrlChecker::new(
+            url_jobs,
+            url_host_jobs,
+            Duration::from_millis(url_delay),
+            timeout,
+        ) {
+            Ok(checker) => Some(Arc::new(checker)),
             Err(err) => {
-                eprintln!("{}", err);
+                eprintln!("Failed to initialize URL checker: {}", err);
                 std::process::exit(1);
             }
         }
@@ -168,15 +821,17 @@ fn main() {
         None
     };
 
-    let success_store = if let Some(success_path) = success_path {
-        match OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(success_path)
-        {
-            Ok(f) => Some(Arc::new(f)),
+    let cache = if let Some(cache_dir) = cache_dir {
+        // results of one shellcheck version aren't reused by another
+        let salt = shellcheck_version().unwrap_or_default();
+        match Cache::new(&cache_dir, salt) {
+            Ok(cache) => Some(Arc::new(cache)),
             Err(err) => {
-                eprintln!("{}", err);
+                eprintln!(
+                    "Failed to create cache directory {}: {}",
+                    cache_dir.display(),
+                    err
+                );
                 std::process::exit(1);
             }
         }
@@ -184,75 +839,505 @@ fn main() {
         None
     };
 
-    let logger_handle = thread::spawn(move || {
-        let show_log = parallel.is_none();
-        while let Ok(log) = rx.recv() {
-            match log {
-                LogMessage::Info(msg) if show_log => {
-                    println!("{}", msg);
+    let unique_ids = check_unique.then(|| Arc::new(UniqueIds::new()));
+    let field_shapes = check_field_shapes.then(|| Arc::new(FieldShapes::new()));
+    let duplicate_configs = check_duplicates.then(|| Arc::new(DuplicateConfigs::new()));
+    // warnings are only known through the report, so it is also kept when
+    // they fail the file
+    let report = (format != OutputFormat::Text || report_dir.is_some() || fail_on == FailOn::Warn)
+        .then(|| Arc::new(Report::new()));
+
+    // workers beyond the number of files would never get one, so a huge
+    // --parallel doesn't start threads for nothing
+    let jobs = jobs.min(files.len() * repeat).max(1);
+
+    if let Some(ref op_log) = op_log {
+        op_log.info(format!(
+            "linting {} file(s) with {} worker(s)",
+            files.len(),
+            jobs
+        ));
+    }
+    // files done by an earlier run keep their outcome and aren't dispatched
+    if let Some(ref checkpoint) = checkpoint {
+        let mut resumed = 0;
+        for file_path in &files {
+            match checkpoint.outcome(file_path) {
+                Some(true) => success.fetch_add(1, Ordering::SeqCst),
+                Some(false) => fail.fetch_add(1, Ordering::SeqCst),
+                None => continue,
+            };
+            resumed += 1;
+        }
+        if resumed > 0 && !quiet {
+            println!("Skipping {} file(s) done in an earlier run", resumed);
+        }
+        if let Some(ref op_log) = op_log {
+            op_log.info(format!("resuming with {} file(s) already done", resumed));
+        }
+    }
+
+    // only kept when repeating, as the timings of every run of each file
+    let timings =
+        (repeat > 1).then(|| Arc::new(Mutex::new(HashMap::<String, Vec<Duration>>::new())));
+    // lints one file and records its outcome, whichever model runs it. The
+    // job started waiting to be picked up at `wait`, and carries the index of
+    // the run, where only the first one is reported.
+    let process = {
+        let success = Arc::clone(&success);
+        let fail = Arc::clone(&fail);
+        let success_store = success_store.clone();
+        let fail_store = fail_store.clone();
+        let profile = profile.clone();
+        let url_checker = url_checker.clone();
+        let cache = cache.clone();
+        let options = options.clone();
+        let unique_ids = unique_ids.clone();
+        let field_shapes = field_shapes.clone();
+        let duplicate_configs = duplicate_configs.clone();
+        let report = report.clone();
+        let out_dir = out_dir.clone();
+        let report_dir = report_dir.clone();
+        let op_log = op_log.clone();
+        let timings = timings.clone();
+        let checkpoint = checkpoint.clone();
+        let completed_tx = completed_tx.clone();
+
+        Arc::new(
+            move |file_path: String, file_tx: Sender<LogMessage>, run: usize, wait: Instant| {
+                let start = Instant::now();
+                if let Some(ref op_log) = op_log {
+                    op_log.debug(format!(
+                        "{}: picked up after waiting {:?}",
+                        file_path,
+                        start - wait
+                    ));
                 }
-                LogMessage::Error(msg) if show_log => {
-                    eprintln!("[{}] {}", &*CROSS_MARK, msg);
+
+                let (file_tx, completed_rx) = match completed_tx {
+                    Some(_) if run == 0 => {
+                        let (file_tx, file_rx) = sync::mpsc::channel();
+                        (file_tx, Some(file_rx))
+                    }
+                    _ => (file_tx, None),
+                };
+                let logger = LogManager::new(file_tx).create_logger::<PathBuf>(None);
+                let linter = Linter::new(logger.clone(), timeout)
+                    .with_profile(profile.clone())
+                    .with_input_format(input_format)
+                    .with_url_checker(url_checker.clone())
+                    .with_report(report.clone().filter(|_| run == 0))
+                    .with_field_shapes(field_shapes.clone().filter(|_| run == 0))
+                    .with_out_dir(out_dir.clone())
+                    .with_stdout(to_stdout)
+                    .with_dump_config(dump_config)
+                    .with_cache(cache.clone())
+                    .with_op_log(op_log.clone())
+                    .with_options(options.clone());
+                let result = linter.lint(&file_path, inplace, disable_shellcheck, pkgver);
+                if let Some(ref timings) = timings {
+                    if let Ok(mut timings) = timings.lock() {
+                        timings
+                            .entry(file_path.clone())
+                            .or_default()
+                            .push(start.elapsed());
+                    }
                 }
-                LogMessage::Warn(msg) if show_log => {
-                    eprintln!("[{}] {}", &*WARN, msg);
+                if run > 0 {
+                    return;
                 }
-                LogMessage::Success(msg) if show_log => {
-                    println!("[{}] {}", &*CHECK_MARK, msg);
+                let has_warnings = fail_on == FailOn::Warn
+                    && report.as_ref().is_some_and(|report| {
+                        report.result(&file_path).is_some_and(|file_result| {
+                            file_result
+                                .diagnostics
+                                .iter()
+                                .any(|diagnostic| diagnostic.severity == Severity::Warn)
+                        })
+                    });
+                let passed = result.is_some() && !has_warnings;
+                if let Some(ref op_log) = op_log {
+                    op_log.info(format!(
+                        "{}: {} in {:?}",
+                        file_path,
+                        if passed { "passed" } else { "failed" },
+                        start.elapsed()
+                    ));
                 }
-                LogMessage::CustomError(msg) if show_log => {
-                    eprintln!("{}", msg);
+                if let Some(ref report) = report {
+                    report.set_passed(&file_path, passed);
+                    if let Some(ref report_dir) = report_dir {
+                        if let Some(file_result) = report.result(&file_path) {
+                            if let Err(err) = file_result.write_to_dir(report_dir, format) {
+                                logger.error(format!(
+                                    "Failed to write report for {}: {}",
+                                    file_path, err
+                                ));
+                            }
+                        }
+                    }
+                    match format {
+                        OutputFormat::Ndjson => {
+                            if let Some(file_result) = report.take_result(&file_path) {
+                                match file_result.ndjson_lines(group_by) {
+                                    Ok(lines) => {
+                                        lines.into_iter().for_each(|line| logger.result(line))
+                                    }
+                                    Err(err) => eprintln!("Failed to serialize result: {}", err),
+                                }
+                            }
+                        }
+                        // nothing is written at the end, so the result isn't kept
+                        OutputFormat::Text => {
+                            report.take_result(&file_path);
+                        }
+                        OutputFormat::Json | OutputFormat::Checkstyle | OutputFormat::Junit => {}
+                    }
+                }
+                if let Some(ref checkpoint) = checkpoint {
+                    if let Err(err) = checkpoint.record(&file_path, passed) {
+                        logger.error(format!(
+                            "Failed to record {} in the checkpoint: {}",
+                            file_path, err
+                        ));
+                    }
+                }
+                if let (Some(config), Some(unique_ids)) = (&result, &unique_ids) {
+                    unique_ids.record(&file_path, config);
+                }
+                if let (Some(config), Some(duplicate_configs)) = (&result, &duplicate_configs) {
+                    duplicate_configs.record(&file_path, config);
+                }
+                if passed {
+                    if let Some(mut success_store) = success_store.as_deref() {
+                        let fp = format!("{}\n", file_path);
+                        let _ = success_store.write_all(fp.as_bytes());
+                    }
+                    success.fetch_add(1, Ordering::SeqCst);
+                } else {
+                    if let Some(mut fail_store) = fail_store.as_deref() {
+                        let fp = format!("{}\n", file_path);
+                        let _ = fail_store.write_all(fp.as_bytes());
+                    }
+                    fail.fetch_add(1, Ordering::SeqCst);
+                }
+                if let (Some(completed_tx), Some(completed_rx)) = (&completed_tx, completed_rx) {
+                    let _ = completed_tx.send(completed_rx);
+                }
+            },
+        )
+    };
+
+    // hands the files to `send` in input order, until it returns false
+    let dispatch = |send: &mut dyn FnMut(String, Sender<LogMessage>, usize) -> bool| {
+        'dispatch: for run in 0..repeat {
+            for file_path in &files {
+                if checkpoint
+                    .as_ref()
+                    .is_some_and(|checkpoint| checkpoint.outcome(file_path).is_some())
+                {
+                    continue;
+                }
+                let file_tx = match shared_tx {
+                    // the output of repeated runs is discarded, and jobs
+                    // logging as they complete open their own channel
+                    _ if run > 0 || completed_tx.is_some() => sync::mpsc::channel().0,
+                    Some(ref shared_tx) => shared_tx.clone(),
+                    None => {
+                        let (file_tx, file_rx) = sync::mpsc::channel();
+                        let _ = tx.send(file_rx);
+                        file_tx
+                    }
+                };
+                if !send(file_path.clone(), file_tx, run) {
+                    break 'dispatch;
+                }
+                if let Some(ref op_log) = op_log {
+                    op_log.debug(format!("{}: dispatched for run {}", file_path, run + 1));
                 }
-                LogMessage::Done => break,
-                _ => {}
             }
         }
-    });
+    };
 
-    let semaphore = Arc::new(Semaphore::new(parallel.unwrap_or(1)));
-    let mut handles = Vec::new();
+    match concurrency_model {
+        // files are handed to a fixed pool of workers through a bounded
+        // queue, so what is held for pending files stays proportional to the
+        // number of jobs rather than the number of files
+        ConcurrencyModel::Threadpool => {
+            let (job_tx, job_rx) =
+                sync::mpsc::sync_channel::<(String, Sender<LogMessage>, usize)>(jobs);
+            let job_rx = Arc::new(Mutex::new(job_rx));
+            let mut workers = Vec::with_capacity(jobs);
 
-    for file_path in &files {
-        let file_path = file_path.clone();
-        let semaphore = Arc::clone(&semaphore);
-        let success = Arc::clone(&success);
-        let logger = log_manager.create_logger::<PathBuf>(None);
-        let fail = Arc::clone(&fail);
-        let success_store = success_store.clone();
-        let fail_store = fail_store.clone();
+            for index in 0..jobs {
+                let job_rx = Arc::clone(&job_rx);
+                let process = Arc::clone(&process);
+                let op_log = op_log.clone();
 
-        semaphore.acquire();
-        let handle = thread::spawn(move || {
-            let linter = Linter::new(logger, Duration::from_secs(timeout as u64));
-            if linter
-                .lint(&file_path, inplace, disable_shellcheck, pkgver)
-                .is_some()
+                let worker = thread::Builder::new().name(format!("worker-{}", index + 1));
+                let worker = worker.spawn(move || loop {
+                    let wait = Instant::now();
+                    let job = job_rx.lock().unwrap().recv();
+                    let Ok((file_path, file_tx, run)) = job else {
+                        if let Some(ref op_log) = op_log {
+                            op_log.debug("queue closed, stopping");
+                        }
+                        break;
+                    };
+                    process(file_path, file_tx, run, wait);
+                });
+
+                workers.push(worker.expect("Failed to spawn worker"));
+            }
+
+            dispatch(&mut |file_path, file_tx, run| job_tx.send((file_path, file_tx, run)).is_ok());
+            drop(job_tx);
+
+            for worker in workers {
+                worker.join().unwrap();
+            }
+        }
+        // every file is queued on a work-stealing pool up front, which
+        // balances uneven files better at the cost of holding all the jobs
+        ConcurrencyModel::Rayon => {
+            let pool = match rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .thread_name(|index| format!("worker-{}", index + 1))
+                .build()
             {
-                if let Some(mut success_store) = success_store {
-                    let fp = format!("{}\n", file_path);
-                    let _ = success_store.write_all(fp.as_bytes());
+                Ok(pool) => pool,
+                Err(err) => {
+                    eprintln!(
+                        "[{}] Failed to start the thread pool: {}",
+                        &*CROSS_MARK, err
+                    );
+                    std::process::exit(1);
                 }
-                success.fetch_add(1, Ordering::SeqCst);
-            } else {
-                if let Some(mut fail_store) = fail_store {
-                    let fp = format!("{}\n", file_path);
-                    let _ = fail_store.write_all(fp.as_bytes());
+            };
+            pool.in_place_scope(|scope| {
+                dispatch(&mut |file_path, file_tx, run| {
+                    let process = Arc::clone(&process);
+                    let wait = Instant::now();
+                    scope.spawn(move |_| process(file_path, file_tx, run, wait));
+                    true
+                });
+            });
+        }
+    }
+    if let Some(ref op_log) = op_log {
+        op_log.info(format!(
+            "{} passed, {} failed in {:?}",
+            success.load(Ordering::SeqCst),
+            fail.load(Ordering::SeqCst),
+            now.elapsed()
+        ));
+    }
+
+    // the jobs hold senders of the logger thread as well
+    drop(process);
+    drop(completed_tx);
+    drop(shared_tx);
+    drop(tx);
+    logger_handle.join().unwrap();
+
+    if let Some(timings) = timings {
+        let timings = timings
+            .lock()
+            .map(|timings| timings.clone())
+            .unwrap_or_default();
+        print_timings(&files, &timings, repeat, now.elapsed());
+    }
+
+    if let (Some(profile), Some(profile_path)) = (profile, &profile_path) {
+        if let Err(err) = profile.write_folded(profile_path) {
+            eprintln!("Failed to write profile to {}: {}", profile_path, err);
+        }
+    }
+
+    // streamed results are already written, so the findings of the
+    // cross-file checks only go to the report for the formats combining every
+    // file into one document, and to stderr like in the text output otherwise
+    let combined = matches!(
+        format,
+        OutputFormat::Json | OutputFormat::Checkstyle | OutputFormat::Junit
+    );
+    let combined_report = report.as_deref().filter(|_| combined);
+
+    let mut collided = false;
+    if let Some(unique_ids) = unique_ids {
+        for collision in unique_ids.collisions() {
+            collided = true;
+            let diagnostics = collision.files.iter().map(|file| {
+                let others: Vec<&str> = collision
+                    .files
+                    .iter()
+                    .filter(|other| *other != file)
+                    .map(String::as_str)
+                    .collect();
+                let diagnostic = ErrorDetails {
+                    rule: "duplicate-id",
+                    field: collision.field.to_string(),
+                    message: format!(
+                        "'{}' is also used by {}",
+                        collision.value,
+                        others.join(", ")
+                    ),
+                    line_number: 0,
+                    severity: Severity::Error,
+                };
+                (file.as_str(), diagnostic)
+            });
+            report_cross_file(combined_report, count_only, diagnostics, || {
+                eprintln!(
+                    "[{}] {} '{}' is used by {} files:",
+                    &*CROSS_MARK,
+                    collision.field.bold(),
+                    collision.value,
+                    collision.files.len()
+                );
+                for file in &collision.files {
+                    eprintln!("    {}", file);
                 }
-                fail.fetch_add(1, Ordering::SeqCst);
-            }
+            });
+        }
+    }
 
-            semaphore.release();
-        });
+    let mut inconsistent = false;
+    if let Some(field_shapes) = field_shapes {
+        for inconsistency in field_shapes.inconsistencies() {
+            inconsistent = true;
+            let (common, common_files) = &inconsistency.shapes[0];
+            let diagnostics = inconsistency.shapes[1..].iter().flat_map(|(shape, files)| {
+                files.iter().map(|file| {
+                    let diagnostic = ErrorDetails {
+                        rule: "inconsistent-field-shape",
+                        field: inconsistency.field.clone(),
+                        message: format!(
+                            "'{}' is written as {} here, but as {} in {} other file(s)",
+                            inconsistency.field,
+                            shape.as_str(),
+                            common.as_str(),
+                            common_files.len()
+                        ),
+                        line_number: 0,
+                        severity: Severity::Warn,
+                    };
+                    (file.as_str(), diagnostic)
+                })
+            });
+            report_cross_file(combined_report, count_only, diagnostics, || {
+                let counts: Vec<String> = inconsistency
+                    .shapes
+                    .iter()
+                    .map(|(shape, files)| {
+                        format!("as {} in {} file(s)", shape.as_str(), files.len())
+                    })
+                    .collect();
+                eprintln!(
+                    "[{}] {} is written {}:",
+                    &*WARN,
+                    inconsistency.field.bold(),
+                    counts.join(", ")
+                );
+                for (shape, files) in &inconsistency.shapes[1..] {
+                    for file in files {
+                        eprintln!("    {} ({})", file, shape.as_str());
+                    }
+                }
+            });
+        }
+    }
 
-        handles.push(handle);
+    let mut duplicated = false;
+    if let Some(duplicate_configs) = duplicate_configs {
+        for group in duplicate_configs.groups() {
+            duplicated = true;
+            let diagnostics = group.iter().map(|file| {
+                let others: Vec<&str> = group
+                    .iter()
+                    .filter(|other| *other != file)
+                    .map(String::as_str)
+                    .collect();
+                let diagnostic = ErrorDetails {
+                    rule: "duplicate-config",
+                    field: "file".to_string(),
+                    message: format!("The config is identical to {}", others.join(", ")),
+                    line_number: 0,
+                    severity: Severity::Warn,
+                };
+                (file.as_str(), diagnostic)
+            });
+            report_cross_file(combined_report, count_only, diagnostics, || {
+                eprintln!("[{}] {} files have identical configs:", &*WARN, group.len());
+                for file in &group {
+                    eprintln!("    {}", file);
+                }
+            });
+        }
     }
 
-    for handle in handles {
-        handle.join().unwrap();
+    // collisions are errors, but aren't tied to a single file, and neither
+    // are inconsistent shapes and duplicate configs, which only fail the run
+    // with --fail-on warn
+    let failed = fail.load(Ordering::SeqCst) > 0
+        || collided
+        || ((inconsistent || duplicated) && fail_on == FailOn::Warn);
+    let exit_code = i32::from(failed && fail_on != FailOn::None);
+
+    if let Some(ref run_report_path) = run_report_path {
+        let run_report = RunReport {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            args: env::args().skip(1).collect(),
+            hostname: hostname(),
+            timestamp: started
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            shellcheck_version: (!disable_shellcheck).then(shellcheck_version).flatten(),
+            files: files.len(),
+            passed: success.load(Ordering::SeqCst),
+            failed: fail.load(Ordering::SeqCst),
+            duration_secs: now.elapsed().as_secs_f64(),
+            exit_code,
+        };
+        if let Err(err) = run_report.write_to_file(run_report_path) {
+            eprintln!(
+                "Failed to write run report {}: {}",
+                run_report_path.display(),
+                err
+            );
+        }
     }
 
-    log_manager.done();
-    logger_handle.join().unwrap();
+    if let (Some(report), false) = (report, format == OutputFormat::Text) {
+        let result = match format {
+            OutputFormat::Json => report.write_json(io::stdout().lock(), group_by),
+            OutputFormat::Checkstyle => report.write_checkstyle(io::stdout().lock()),
+            OutputFormat::Junit => report.write_junit(now.elapsed(), io::stdout().lock()),
+            OutputFormat::Ndjson | OutputFormat::Text => Ok(()),
+        };
+        if let Err(err) = result {
+            eprintln!("Failed to write report: {}", err);
+            std::process::exit(1);
+        }
+        exit(exit_code);
+    }
+
+    if to_stdout || no_summary {
+        exit(exit_code);
+    }
+
+    if count_only {
+        println!(
+            "{} passed, {} failed",
+            success.load(Ordering::SeqCst),
+            fail.load(Ordering::SeqCst)
+        );
+        exit(exit_code);
+    }
+
+    if verbosity == Verbosity::Quiet {
+        exit(exit_code);
+    }
 
     println!();
     println!(
@@ -273,4 +1358,65 @@ fn main() {
         files.len(),
         now.elapsed()
     );
+    exit(exit_code);
+}
+
+// Records the diagnostics of a cross-file finding on the files involved if
+// there is a combined report, or prints the finding unless only counts are
+// shown.
+fn report_cross_file<'a>(
+    report: Option<&Report>,
+    count_only: bool,
+    diagnostics: impl Iterator<Item = (&'a str, ErrorDetails)>,
+    print: impl FnOnce(),
+) {
+    if let Some(report) = report {
+        for (file, diagnostic) in diagnostics {
+            report.record(file, [diagnostic]);
+        }
+    } else if !count_only {
+        print();
+    }
+}
+
+// Prints the min, median and max time spent on each file over the runs, to
+// stderr so it doesn't mix with machine-readable output.
+fn print_timings(
+    files: &IndexSet<String>,
+    timings: &HashMap<String, Vec<Duration>>,
+    repeat: usize,
+    total: Duration,
+) {
+    eprintln!();
+    eprintln!(
+        "[{}] Timings over {} runs (min / median / max):",
+        "+".bright_blue().bold(),
+        repeat
+    );
+    for file_path in files {
+        let Some(durations) = timings.get(file_path) else {
+            continue;
+        };
+        let mut durations = durations.clone();
+        durations.sort();
+        eprintln!(
+            "    {}: {:?} / {:?} / {:?}",
+            file_path,
+            durations[0],
+            durations[durations.len() / 2],
+            durations[durations.len() - 1]
+        );
+    }
+    eprintln!(
+        "[{}] Total {:?}, {:?} per run on average",
+        "+".bright_blue().bold(),
+        total,
+        total / repeat as u32
+    );
+}
+
+// Exits without running destructors, so stdout is flushed first.
+fn exit(code: i32) -> ! {
+    let _ = io::stdout().flush();
+    process::exit(code)
 }
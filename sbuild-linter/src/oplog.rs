@@ -0,0 +1,97 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, LineWriter, Write},
+    path::Path,
+    str::FromStr,
+    sync::Mutex,
+    thread,
+    time::Instant,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OpLevel {
+    Info,
+    Debug,
+}
+
+impl OpLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpLevel::Info => "info",
+            OpLevel::Debug => "debug",
+        }
+    }
+}
+
+impl FromStr for OpLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(OpLevel::Info),
+            "debug" => Ok(OpLevel::Debug),
+            _ => Err(format!("Invalid log level: '{}'", s)),
+        }
+    }
+}
+
+// Log of what the linter itself does, e.g. which worker picked up a file and
+// how long its phases took, kept apart from the diagnostics. Every line starts
+// with the seconds since the log was created and the name of the thread.
+pub struct OpLog {
+    level: OpLevel,
+    start: Instant,
+    // written line by line, so the log is complete up to the last event even
+    // if the run is interrupted
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl OpLog {
+    pub fn create<P: AsRef<Path>>(path: P, level: OpLevel) -> io::Result<Self> {
+        Ok(OpLog {
+            level,
+            start: Instant::now(),
+            writer: Mutex::new(Box::new(LineWriter::new(File::create(path)?))),
+        })
+    }
+
+    // A log shown on stderr, along with the diagnostics, instead of written
+    // to a file.
+    pub fn stderr(level: OpLevel) -> Self {
+        OpLog {
+            level,
+            start: Instant::now(),
+            writer: Mutex::new(Box::new(io::stderr())),
+        }
+    }
+
+    pub fn enabled(&self, level: OpLevel) -> bool {
+        level <= self.level
+    }
+
+    pub fn log(&self, level: OpLevel, message: impl Display) {
+        if !self.enabled(level) {
+            return;
+        }
+        let thread = thread::current();
+        let line = format!(
+            "{:>10.6} {:<5} [{}] {}\n",
+            self.start.elapsed().as_secs_f64(),
+            level.as_str(),
+            thread.name().unwrap_or("unnamed"),
+            message
+        );
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writer.write_all(line.as_bytes());
+        }
+    }
+
+    pub fn info(&self, message: impl Display) {
+        self.log(OpLevel::Info, message);
+    }
+
+    pub fn debug(&self, message: impl Display) {
+        self.log(OpLevel::Debug, message);
+    }
+}
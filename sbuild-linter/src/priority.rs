@@ -0,0 +1,120 @@
+use std::{io, str::FromStr};
+
+// Number of CPUs an affinity mask can hold. CPU lists are checked against it
+// while parsing, so a range like `0-18446744073709551615` is rejected before
+// it's expanded.
+#[cfg(target_os = "linux")]
+const MAX_CPUS: usize = libc::CPU_SETSIZE as usize;
+#[cfg(not(target_os = "linux"))]
+const MAX_CPUS: usize = 1024;
+
+// CPUs the process may run on, written like `0-3,6`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuList(pub Vec<usize>);
+
+impl FromStr for CpuList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cpus = Vec::new();
+        for part in s.split(',').map(str::trim) {
+            let parse = |cpu: &str| {
+                cpu.trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid CPU list: '{}'", s))
+            };
+            let (first, last) = match part.split_once('-') {
+                Some((first, last)) => (parse(first)?, parse(last)?),
+                None => (parse(part)?, parse(part)?),
+            };
+            if first > last {
+                return Err(format!("Invalid CPU list: '{}'", s));
+            }
+            if last >= MAX_CPUS {
+                return Err(format!(
+                    "Invalid CPU list: '{}', CPUs go up to {}",
+                    s,
+                    MAX_CPUS - 1
+                ));
+            }
+            cpus.extend(first..=last);
+        }
+        cpus.sort_unstable();
+        cpus.dedup();
+        Ok(CpuList(cpus))
+    }
+}
+
+// Changes the nice level of the process. Threads and child processes started
+// afterwards inherit it, so it has to be set before the workers are spawned.
+#[cfg(unix)]
+pub fn set_nice(nice: i32) -> io::Result<()> {
+    // the calling thread on Linux, the whole process elsewhere
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn set_nice(_nice: i32) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "not supported on this platform",
+    ))
+}
+
+// Restricts the process to the given CPUs, inherited like the nice level.
+#[cfg(target_os = "linux")]
+pub fn set_cpu_affinity(cpus: &CpuList) -> io::Result<()> {
+    let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+    for &cpu in &cpus.0 {
+        if cpu >= MAX_CPUS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("CPU {} is out of range", cpu),
+            ));
+        }
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    let result =
+        unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_cpu_affinity(_cpus: &CpuList) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_lists_are_expanded_sorted_and_deduplicated() {
+        assert_eq!("6,0-3,2".parse(), Ok(CpuList(vec![0, 1, 2, 3, 6])));
+        assert_eq!(" 1 - 2 ".parse(), Ok(CpuList(vec![1, 2])));
+        assert!("3-1".parse::<CpuList>().is_err());
+        assert!("1,,2".parse::<CpuList>().is_err());
+        assert!("a-b".parse::<CpuList>().is_err());
+    }
+
+    #[test]
+    fn cpu_lists_are_bounded_before_expanding() {
+        let last = MAX_CPUS - 1;
+        assert_eq!(
+            format!("{}", last).parse::<CpuList>().map(|cpus| cpus.0),
+            Ok(vec![last])
+        );
+        assert!(format!("{}", MAX_CPUS).parse::<CpuList>().is_err());
+        assert!(format!("0-{}", usize::MAX).parse::<CpuList>().is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+struct Sample {
+    file_path: String,
+    phase: &'static str,
+    duration: Duration,
+}
+
+#[derive(Default)]
+pub struct Profile {
+    samples: Mutex<Vec<Sample>>,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, file_path: &str, phase: &'static str, start: Instant) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.push(Sample {
+                file_path: file_path.to_string(),
+                phase,
+                duration: start.elapsed(),
+            });
+        }
+    }
+
+    // Writes the samples in the folded stacks format understood by
+    // flamegraph tooling (`inferno-flamegraph`, `flamegraph.pl`), one line
+    // per phase with the duration in microseconds as the sample count.
+    pub fn write_folded<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        let samples = self
+            .samples
+            .lock()
+            .map_err(|_| io::Error::other("Failed to acquire lock on profile"))?;
+        for sample in samples.iter() {
+            writeln!(
+                writer,
+                "sbuild-linter;{};{} {}",
+                sample.file_path.replace([';', ' '], "_"),
+                sample.phase,
+                sample.duration.as_micros()
+            )?;
+        }
+
+        writer.flush()
+    }
+}
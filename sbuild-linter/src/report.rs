@@ -0,0 +1,468 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    time::Duration,
+};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{ErrorDetails, Severity},
+    mirrored_path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+    Checkstyle,
+    Junit,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "checkstyle" => Ok(OutputFormat::Checkstyle),
+            "junit" => Ok(OutputFormat::Junit),
+            _ => Err(format!("Invalid output format: '{}'", s)),
+        }
+    }
+}
+
+// Shape of the JSON output: one object per file with its diagnostics, or one
+// object per diagnostic carrying the path of its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    File,
+    Diagnostic,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(GroupBy::File),
+            "diagnostic" => Ok(GroupBy::Diagnostic),
+            _ => Err(format!("Invalid grouping: '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub rule: String,
+    pub severity: Severity,
+    pub field: String,
+    pub message: String,
+    // 0 when the diagnostic isn't tied to a line
+    pub line: usize,
+}
+
+impl From<&ErrorDetails> for Diagnostic {
+    fn from(error: &ErrorDetails) -> Self {
+        Diagnostic {
+            rule: error.rule.to_string(),
+            severity: error.severity,
+            field: error.field.clone(),
+            message: error.message.clone(),
+            line: error.line_number,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileResult {
+    pub path: String,
+    pub passed: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// A diagnostic written on its own with `--group-by diagnostic`.
+#[derive(Debug, Serialize)]
+pub struct FileDiagnostic<'a> {
+    pub path: &'a str,
+    #[serde(flatten)]
+    pub diagnostic: &'a Diagnostic,
+}
+
+// The document written by `--format json --group-by diagnostic`.
+#[derive(Debug, Serialize)]
+struct DiagnosticResults<'a> {
+    diagnostics: Vec<FileDiagnostic<'a>>,
+}
+
+impl FileResult {
+    pub fn file_diagnostics(&self) -> impl Iterator<Item = FileDiagnostic<'_>> {
+        self.diagnostics.iter().map(|diagnostic| FileDiagnostic {
+            path: &self.path,
+            diagnostic,
+        })
+    }
+
+    // Serializes the result as NDJSON lines, a single one for the file or one
+    // per diagnostic, in which case a file without any has no line.
+    pub fn ndjson_lines(&self, group_by: GroupBy) -> serde_json::Result<Vec<String>> {
+        match group_by {
+            GroupBy::File => Ok(vec![serde_json::to_string(self)?]),
+            GroupBy::Diagnostic => self
+                .file_diagnostics()
+                .map(|diagnostic| serde_json::to_string(&diagnostic))
+                .collect(),
+        }
+    }
+
+    pub fn write_text<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let status = if self.passed { "passed" } else { "failed" };
+        writeln!(writer, "{}: {}", self.path, status)?;
+        for diagnostic in &self.diagnostics {
+            writeln!(writer, "{}", self.describe(diagnostic))?;
+        }
+        writer.flush()
+    }
+
+    // One line for the diagnostic, e.g. `a.yaml:3: warn [rule] field -> message`.
+    fn describe(&self, diagnostic: &Diagnostic) -> String {
+        let location = if diagnostic.line != 0 {
+            format!("{}:{}", self.path, diagnostic.line)
+        } else {
+            self.path.clone()
+        };
+        format!(
+            "{}: {} [{}] {} -> {}",
+            location,
+            diagnostic.severity.as_str(),
+            diagnostic.rule,
+            diagnostic.field,
+            diagnostic.message
+        )
+    }
+
+    // Writes the result to its own file under `dir`, named after the input
+    // with an extension for the format, e.g. `pkgs/a.yaml.json`.
+    pub fn write_to_dir(&self, dir: &Path, format: OutputFormat) -> io::Result<PathBuf> {
+        let extension = match format {
+            OutputFormat::Text => "txt",
+            OutputFormat::Json | OutputFormat::Ndjson => "json",
+            OutputFormat::Checkstyle | OutputFormat::Junit => "xml",
+        };
+        let mut file_name = mirrored_path(&self.path).into_os_string();
+        file_name.push(format!(".{}", extension));
+        let path = dir.join(file_name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        match format {
+            OutputFormat::Text => self.write_text(&mut writer)?,
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                serde_json::to_writer_pretty(&mut writer, self)?;
+                writeln!(writer)?;
+            }
+            OutputFormat::Checkstyle => write_checkstyle(std::slice::from_ref(self), &mut writer)?,
+            OutputFormat::Junit => write_junit(std::slice::from_ref(self), None, &mut writer)?,
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+}
+
+// The document written by `--format json`, and read back by `diff`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Results {
+    pub files: Vec<FileResult>,
+}
+
+impl Results {
+    pub fn from_reader<R: io::Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+}
+
+// The document written by `--report`, describing the run as a whole so the
+// results can be traced back to the configuration that produced them.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub version: String,
+    // arguments the linter was started with, without the program name
+    pub args: Vec<String>,
+    pub hostname: Option<String>,
+    // start of the run, in seconds since the Unix epoch
+    pub timestamp: u64,
+    // None when shellcheck was disabled or isn't installed
+    pub shellcheck_version: Option<String>,
+    pub files: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration_secs: f64,
+    pub exit_code: i32,
+}
+
+impl RunReport {
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+#[derive(Default)]
+struct FileEntry {
+    passed: bool,
+    errors: Vec<ErrorDetails>,
+}
+
+// Collects the diagnostics of every linted file so they can be written in a
+// machine-readable format once the batch is done.
+#[derive(Default)]
+pub struct Report {
+    files: Mutex<IndexMap<String, FileEntry>>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Registers the file so it shows up in the report even without
+    // diagnostics.
+    pub fn add_file(&self, file_path: &str) {
+        if let Ok(mut files) = self.files.lock() {
+            files.entry(file_path.to_string()).or_default();
+        }
+    }
+
+    pub fn record(&self, file_path: &str, errors: impl IntoIterator<Item = ErrorDetails>) {
+        if let Ok(mut files) = self.files.lock() {
+            files
+                .entry(file_path.to_string())
+                .or_default()
+                .errors
+                .extend(errors);
+        }
+    }
+
+    pub fn set_passed(&self, file_path: &str, passed: bool) {
+        if let Ok(mut files) = self.files.lock() {
+            files.entry(file_path.to_string()).or_default().passed = passed;
+        }
+    }
+
+    pub fn result(&self, file_path: &str) -> Option<FileResult> {
+        let files = self.files.lock().ok()?;
+        let entry = files.get(file_path)?;
+        Some(FileResult {
+            path: file_path.to_string(),
+            passed: entry.passed,
+            diagnostics: entry.errors.iter().map(Diagnostic::from).collect(),
+        })
+    }
+
+    // Removes the file from the report and returns its result, so it can be
+    // written out as soon as the file is done.
+    pub fn take_result(&self, file_path: &str) -> Option<FileResult> {
+        let result = self.result(file_path)?;
+        if let Ok(mut files) = self.files.lock() {
+            files.shift_remove(file_path);
+        }
+        Some(result)
+    }
+
+    // Removes the file from the report and returns its diagnostics as they
+    // were recorded.
+    pub fn take_errors(&self, file_path: &str) -> Vec<ErrorDetails> {
+        let Ok(mut files) = self.files.lock() else {
+            return Vec::new();
+        };
+        files
+            .shift_remove(file_path)
+            .map(|entry| entry.errors)
+            .unwrap_or_default()
+    }
+
+    // Returns the collected results with the files sorted by path, so the
+    // output doesn't depend on the order the jobs finished in.
+    pub fn results(&self) -> Results {
+        let Ok(files) = self.files.lock() else {
+            return Results::default();
+        };
+
+        let mut files: Vec<FileResult> = files
+            .iter()
+            .map(|(path, entry)| FileResult {
+                path: path.clone(),
+                passed: entry.passed,
+                diagnostics: entry.errors.iter().map(Diagnostic::from).collect(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Results { files }
+    }
+
+    pub fn write_json<W: Write>(&self, mut writer: W, group_by: GroupBy) -> io::Result<()> {
+        let results = self.results();
+        match group_by {
+            GroupBy::File => serde_json::to_writer_pretty(&mut writer, &results)?,
+            GroupBy::Diagnostic => {
+                let diagnostics: Vec<FileDiagnostic> = results
+                    .files
+                    .iter()
+                    .flat_map(FileResult::file_diagnostics)
+                    .collect();
+                serde_json::to_writer_pretty(&mut writer, &DiagnosticResults { diagnostics })?;
+            }
+        }
+        writeln!(writer)?;
+        writer.flush()
+    }
+
+    pub fn write_checkstyle<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_checkstyle(&self.results().files, writer)
+    }
+
+    pub fn write_junit<W: Write>(&self, duration: Duration, writer: W) -> io::Result<()> {
+        write_junit(&self.results().files, Some(duration), writer)
+    }
+}
+
+pub fn write_checkstyle<W: Write>(files: &[FileResult], mut writer: W) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+    for file in files {
+        if file.diagnostics.is_empty() {
+            writeln!(writer, r#"  <file name="{}"/>"#, escape_xml(&file.path))?;
+            continue;
+        }
+
+        writeln!(writer, r#"  <file name="{}">"#, escape_xml(&file.path))?;
+        for diagnostic in &file.diagnostics {
+            let line = if diagnostic.line != 0 {
+                format!(r#" line="{}""#, diagnostic.line)
+            } else {
+                String::new()
+            };
+            let severity = match diagnostic.severity {
+                Severity::Warn => "warning",
+                Severity::Error => "error",
+            };
+            let message = format!("{} -> {}", diagnostic.field, diagnostic.message);
+            writeln!(
+                writer,
+                r#"    <error{} severity="{}" message="{}" source="{}"/>"#,
+                line,
+                severity,
+                escape_xml(&message),
+                diagnostic.rule
+            )?;
+        }
+        writeln!(writer, "  </file>")?;
+    }
+    writeln!(writer, "</checkstyle>")?;
+
+    writer.flush()
+}
+
+// Writes a JUnit report with a test case per file, failed files getting a
+// `<failure>` with all their diagnostics. The diagnostics of passed files,
+// i.e. their warnings, go to `<system-out>`.
+pub fn write_junit<W: Write>(
+    files: &[FileResult],
+    duration: Option<Duration>,
+    mut writer: W,
+) -> io::Result<()> {
+    let failures = files.iter().filter(|file| !file.passed).count();
+    let time = duration
+        .map(|duration| format!(r#" time="{:.3}""#, duration.as_secs_f64()))
+        .unwrap_or_default();
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<testsuite name="sbuild-linter" tests="{}" failures="{}" errors="0" skipped="0"{}>"#,
+        files.len(),
+        failures,
+        time
+    )?;
+    for file in files {
+        let name = escape_xml(&file.path);
+        if file.diagnostics.is_empty() {
+            writeln!(
+                writer,
+                r#"  <testcase name="{}" classname="sbuild-linter"/>"#,
+                name
+            )?;
+            continue;
+        }
+
+        writeln!(
+            writer,
+            r#"  <testcase name="{}" classname="sbuild-linter">"#,
+            name
+        )?;
+        let details: Vec<String> = file
+            .diagnostics
+            .iter()
+            .map(|diagnostic| file.describe(diagnostic))
+            .collect();
+        let details = escape_xml_text(&details.join("\n"));
+        if file.passed {
+            writeln!(writer, "    <system-out>{}</system-out>", details)?;
+        } else {
+            // the first error names the failure, or the first warning when
+            // warnings fail the file
+            let first = file
+                .diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.severity == Severity::Error)
+                .unwrap_or(&file.diagnostics[0]);
+            writeln!(
+                writer,
+                r#"    <failure message="{}" type="{}">{}</failure>"#,
+                escape_xml(&format!("{} -> {}", first.field, first.message)),
+                first.rule,
+                details
+            )?;
+        }
+        writeln!(writer, "  </testcase>")?;
+    }
+    writeln!(writer, "</testsuite>")?;
+
+    writer.flush()
+}
+
+// Escapes element content, where newlines are kept as they are.
+fn escape_xml_text(value: &str) -> String {
+    value
+        .split('\n')
+        .map(escape_xml)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
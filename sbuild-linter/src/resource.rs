@@ -1,7 +1,4 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter, Write},
-};
+use std::io::{self, Write};
 
 #[derive(Debug, Clone)]
 pub struct Resource {
@@ -11,7 +8,7 @@ pub struct Resource {
 }
 
 impl Resource {
-    pub fn write_yaml(&self, writer: &mut BufWriter<File>, indent: usize) -> io::Result<()> {
+    pub fn write_yaml<W: Write>(&self, writer: &mut W, indent: usize) -> io::Result<()> {
         let indent_str = " ".repeat(indent);
 
         if let Some(ref value) = self.url {
@@ -0,0 +1,515 @@
+use crate::error::Severity;
+
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+}
+
+impl Rule {
+    const fn new(
+        id: &'static str,
+        severity: Severity,
+        summary: &'static str,
+        explanation: &'static str,
+    ) -> Self {
+        Self {
+            id,
+            severity,
+            summary,
+            explanation,
+        }
+    }
+}
+
+pub const RULES: &[Rule] = &[
+    Rule::new(
+        "not-a-mapping",
+        Severity::Error,
+        "The document is not a mapping of fields",
+        "An SBUILD must be a single mapping of fields at the top level. Sequences, scalars \
+         and empty documents are rejected before any field is validated.",
+    ),
+    Rule::new(
+        "syntax-error",
+        Severity::Error,
+        "The document could not be parsed",
+        "The file is not valid YAML, or valid JSON when linted with `--input-format json`.",
+    ),
+    Rule::new(
+        "missing-shebang",
+        Severity::Warn,
+        "The first line is not `#!/SBUILD`",
+        "YAML recipes are expected to start with the `#!/SBUILD` marker. The first line is \
+         always skipped, so anything else placed there is ignored.",
+    ),
+    Rule::new(
+        "unreadable-file",
+        Severity::Error,
+        "The file could not be read",
+        "The file doesn't exist, isn't readable by the current user, isn't a regular file or \
+         isn't valid UTF-8.",
+    ),
+    Rule::new(
+        "missing-field",
+        Severity::Error,
+        "A required field is missing",
+        "The fields `_disabled`, `pkg`, `description`, `src_url` and `x_exec` must be \
+         present in every recipe.",
+    ),
+    Rule::new(
+        "missing-pkg-type-field",
+        Severity::Error,
+        "A field required by the `pkg_type` is missing",
+        "Desktop image formats (appbundle, appimage, flatimage, gameimage, nixappimage, \
+         runimage) are integrated into the application menu, so they also need `app_id`. \
+         `--gui-types` replaces the list of these formats.",
+    ),
+    Rule::new(
+        "unsupported-format-version",
+        Severity::Error,
+        "`format_version` is not supported by the linter",
+        "`format_version` declares the version of the SBUILD format the file is written \
+         for, version 1 when absent. Versions older than the oldest one the linter \
+         understands are rejected. Newer versions are reported as warnings and validated as \
+         the current version, so fields they added may be reported as unknown.",
+    ),
+    Rule::new(
+        "unknown-field",
+        Severity::Warn,
+        "A field is not part of the SBUILD format",
+        "Unknown fields are dropped from the validated output. This is usually a typo of a \
+         known field. With `--strict-fields` this is reported as an error.",
+    ),
+    Rule::new(
+        "too-many-fields",
+        Severity::Warn,
+        "The document has an unusually large number of fields",
+        "More than `--max-fields` top-level entries (64 by default) usually means the recipe \
+         was produced by a broken generator.",
+    ),
+    Rule::new(
+        "document-too-large",
+        Severity::Warn,
+        "The document is unusually large",
+        "Recipes larger than `--max-size` bytes (512 KiB by default) usually mean the recipe \
+         was produced by a broken generator.",
+    ),
+    Rule::new(
+        "field-order",
+        Severity::Warn,
+        "Fields are not in the recommended order",
+        "With `--field-order`, top-level fields must follow the given order, by default the \
+         one the validated output is written in. Fields not listed may appear anywhere. With \
+         `--fix` the validated output is written in the given order.",
+    ),
+    Rule::new(
+        "duplicate-field",
+        Severity::Error,
+        "A field is defined more than once",
+        "Each field may only appear once in a mapping, including inside `distro_pkg`.",
+    ),
+    Rule::new(
+        "invalid-type",
+        Severity::Error,
+        "A field has the wrong type",
+        "The value doesn't match the type expected for the field, e.g. a string where a \
+         sequence of strings is required. Run `sbuild-linter dump-schema` for the expected \
+         types.",
+    ),
+    Rule::new(
+        "non-canonical-boolean",
+        Severity::Warn,
+        "A boolean field isn't written as `true` or `false`",
+        "Quoted values like `\"false\"` and spellings like `yes`, `off` or `True` are \
+         accepted for boolean fields, but they are easy to misread and are interpreted \
+         differently by other YAML parsers. Use `true` or `false`.",
+    ),
+    Rule::new(
+        "quoted-number",
+        Severity::Warn,
+        "A numeric field is written as a quoted string",
+        "`\"1\"` is a string in YAML, not a number, which tools comparing the value \
+         numerically may not expect. Write the number without quotes. The validated output \
+         always contains the number.",
+    ),
+    Rule::new(
+        "empty-value",
+        Severity::Error,
+        "A field is empty",
+        "Required fields and the entries of most mappings must not be empty or contain only \
+         whitespace. Lists must not have blank entries either, e.g. a bare `-`, which YAML \
+         reads as null.",
+    ),
+    Rule::new(
+        "description-restates-name",
+        Severity::Warn,
+        "The description only restates the package name",
+        "A `description` equal to `pkg` or `pkg_id`, ignoring case, punctuation and \
+         whitespace, adds no information. Describe what the package does instead.",
+    ),
+    Rule::new(
+        "multi-line-description",
+        Severity::Warn,
+        "The description spans several lines",
+        "Indexes show the `description` as a one-line summary. Longer explanations belong \
+         in `note`. Reported as an error with `--strict-description`.",
+    ),
+    Rule::new(
+        "duplicate-value",
+        Severity::Warn,
+        "A list contains the same value more than once",
+        "Duplicates in string lists are removed automatically. URLs differing only in the \
+         case of the scheme or host are considered duplicates. Duplicates in `category` \
+         are reported as errors.",
+    ),
+    Rule::new(
+        "invalid-identifier",
+        Severity::Error,
+        "`pkg`, `pkg_id` or `app_id` contains invalid characters",
+        "Package identifiers may only contain alphanumeric characters, `+`, `-`, `_` and \
+         `.`.",
+    ),
+    Rule::new(
+        "path-unsafe-identifier",
+        Severity::Error,
+        "`pkg`, `pkg_id` or `app_id` can't be used as a file name",
+        "The identifiers are used to build paths, so they may not contain `/`, `\\` or \
+         whitespace, or be `.` or `..`.",
+    ),
+    Rule::new(
+        "invalid-category",
+        Severity::Error,
+        "A category is not part of the known taxonomy",
+        "Every entry in `category` must be one of the categories shipped with the linter. \
+         Run `sbuild-linter list-categories` to print them.",
+    ),
+    Rule::new(
+        "non-canonical-category",
+        Severity::Warn,
+        "A category isn't spelled with its canonical case",
+        "Categories are matched regardless of case, but are displayed as written. Use the \
+         capitalization of the known category, e.g. `Utility` instead of `utility`. With \
+         `--fix` the category is rewritten in the validated output.",
+    ),
+    Rule::new(
+        "too-many-categories",
+        Severity::Warn,
+        "The package has more categories than the limit",
+        "Packages tagged with many categories show up everywhere in the index, which makes \
+         it noisy. Keep the categories that describe the package best. The limit is set \
+         with `--max-categories`.",
+    ),
+    Rule::new(
+        "redundant-category",
+        Severity::Warn,
+        "A category is implied by a more specific one",
+        "Some categories belong to a more general one, e.g. `ActionGame` to `Game` or \
+         `WebBrowser` to `Network`, which is then redundant next to them. Only categories \
+         with a single obvious parent are checked, and the first entry isn't reported with \
+         `--primary-categories`, which requires it to be a general one.",
+    ),
+    Rule::new(
+        "incoherent-category",
+        Severity::Warn,
+        "A category doesn't fit the package type",
+        "Desktop image formats like `appimage` or `flatimage` are launched from the menu as \
+         standalone applications, so categories like `ConsoleOnly`, `Applet` or \
+         `Screensaver` usually mean either the category or the `pkg_type` is wrong. \
+         `--incoherent-categories` replaces the built-in pairs with `pkg_type:Category` \
+         pairs, or disables the check when empty.",
+    ),
+    Rule::new(
+        "invalid-primary-category",
+        Severity::Error,
+        "The first category is not a primary category",
+        "With `--primary-categories`, the first entry in `category` must be one of the given \
+         primary categories, by default the main categories of the freedesktop menu \
+         specification. The following entries may be any known category.",
+    ),
+    Rule::new(
+        "invalid-pkg-type",
+        Severity::Error,
+        "`pkg_type` is not a supported package type",
+        "`pkg_type` must be one of: appbundle, appimage, archive, dynamic, flatimage, gameimage, \
+         nixappimage, runimage, static.",
+    ),
+    Rule::new(
+        "non-canonical-pkg-type",
+        Severity::Warn,
+        "`pkg_type` isn't spelled in lowercase",
+        "A `pkg_type` matching a supported type except for its case, e.g. `AppImage`, is \
+         a typo rather than an unknown type. With `--fix` it is lowercased in the validated \
+         output.",
+    ),
+    Rule::new(
+        "invalid-url",
+        Severity::Error,
+        "A URL is malformed",
+        "URLs must use the http, https or ftp scheme and have a valid host.",
+    ),
+    Rule::new(
+        "folded-url",
+        Severity::Error,
+        "A URL contains whitespace",
+        "A long URL wrapped across lines is folded into a single value with spaces in place \
+         of the line breaks, unless it is a literal block. Keep URLs on a single line.",
+    ),
+    Rule::new(
+        "unclean-url",
+        Severity::Warn,
+        "A `src_url` has a fragment or tracking parameters",
+        "Fragments (`#...`) and tracking parameters like `utm_source` or `fbclid` are not \
+         needed to download a source and usually come from copying the URL from a browser. \
+         `homepage` URLs may have fragments, and git URLs a fragment pinning a revision.",
+    ),
+    Rule::new(
+        "uppercase-url-host",
+        Severity::Warn,
+        "A `homepage` or `src_url` host has uppercase letters",
+        "Hosts are case-insensitive and indexes compare them lowercased, so the same site \
+         spelled differently looks like different URLs. Only the host is lowercased by \
+         `--fix`; paths can be case-sensitive and are kept as they are.",
+    ),
+    Rule::new(
+        "unpinned-git-source",
+        Severity::Warn,
+        "A git `src_url` doesn't pin a revision",
+        "With `--require-git-pin`, `src_url` entries ending with `.git` must pin a tag or \
+         commit in their fragment or query, e.g. `https://host/repo.git#tag=v1.0`, so builds \
+         don't follow the moving default branch. The accepted keys are `tag` and `commit` \
+         unless others are given.",
+    ),
+    Rule::new(
+        "src-url-is-homepage",
+        Severity::Warn,
+        "A `src_url` is the same as the `homepage`",
+        "The homepage is usually not a downloadable source. URLs are compared ignoring the \
+         case of the scheme and host and a trailing slash.",
+    ),
+    Rule::new(
+        "icon-host-mismatch",
+        Severity::Warn,
+        "The icon is hosted on a different site than the homepage",
+        "Icons hotlinked from unrelated sites can change or disappear without the project \
+         noticing. Only checked with `--check-icon-host`, where hosts sharing their last two \
+         labels count as the same site and the given hosts, e.g. CDNs, are always accepted.",
+    ),
+    Rule::new(
+        "invalid-platform",
+        Severity::Error,
+        "An architecture, OS or host is not supported",
+        "`x_exec.arch` entries must be one of aarch64, loongarch64, riscv64 or x86_64, \
+         `x_exec.os` entries one of freebsd, illumos, linux, netbsd, openbsd or redox, and \
+         `x_exec.host` entries an `arch-os` combination of those.",
+    ),
+    Rule::new(
+        "empty-distro-pkg",
+        Severity::Warn,
+        "A `distro_pkg` entry has no packages",
+        "Empty leaf lists in `distro_pkg` are usually left over from a template. Either add \
+         the package names or remove the entry.",
+    ),
+    Rule::new(
+        "invalid-distro-pkg-name",
+        Severity::Warn,
+        "A `distro_pkg` name breaks the naming rules of its distro",
+        "Names under a known distro key, like `debian`, `archlinux`, `alpine`, `fedora` or \
+         `homebrew`, are checked against that distro's package naming rules, e.g. Debian \
+         package names are lowercase. A name valid elsewhere is likely the wrong spelling \
+         for that distro.",
+    ),
+    Rule::new(
+        "no-supported-arch",
+        Severity::Error,
+        "`distro_pkg` has no packages for a supported architecture",
+        "At least one non-empty list in `distro_pkg` must be nested under a supported \
+         architecture (aarch64, loongarch64, riscv64, x86_64) or not be tied to an \
+         architecture at all. Otherwise the package can't be built anywhere.",
+    ),
+    Rule::new(
+        "arch-sources-mismatch",
+        Severity::Warn,
+        "The number of `src_url` entries doesn't fit the architectures of `distro_pkg`",
+        "With `--check-arch-sources`, a package with `distro_pkg` packages for several \
+         architectures must have a single `src_url` shared by all of them or one per \
+         architecture, or with `--check-arch-sources=per-arch` one per architecture only. \
+         Other counts, e.g. three architectures but two sources, usually mean an incomplete \
+         multi-arch config.",
+    ),
+    Rule::new(
+        "non-printable",
+        Severity::Warn,
+        "Free text contains non-printable characters",
+        "`description`, `note` and `maintainer` must not contain control characters or the \
+         Unicode replacement character, which usually indicate an encoding problem.",
+    ),
+    Rule::new(
+        "placeholder-text",
+        Severity::Error,
+        "Free text contains a placeholder left from a template",
+        "`description`, `note` and `maintainer` must not contain markers like `TODO`, \
+         `FIXME`, `TBD`, `XXX` or `lorem ipsum`, or several words in angle \
+         brackets like `<description here>`. Uppercase markers match case, others ignore it. \
+         `--placeholders` replaces the markers.",
+    ),
+    Rule::new(
+        "missing-shell",
+        Severity::Error,
+        "The `x_exec.shell` interpreter is not installed",
+        "The shell used to run the build script must be available on the system running \
+         the linter.",
+    ),
+    Rule::new(
+        "undefined-alias",
+        Severity::Error,
+        "An alias refers to an undefined anchor",
+        "Every `*name` alias must refer to an `&name` anchor defined earlier in the \
+         document.",
+    ),
+    Rule::new(
+        "unused-anchor",
+        Severity::Warn,
+        "An anchor is defined but never used",
+        "The anchor can be removed, or an alias referring to it is misspelled.",
+    ),
+    Rule::new(
+        "merge-only-field",
+        Severity::Warn,
+        "A required field is only provided through a merge key",
+        "Required fields should be defined explicitly so the recipe can be understood \
+         without resolving `<<` merge keys.",
+    ),
+    Rule::new(
+        "shellcheck",
+        Severity::Error,
+        "The build script has shellcheck findings",
+        "`x_exec.run` and `x_exec.pkgver` are checked with shellcheck. Findings at error \
+         severity fail validation, or at the severity given with `--shellcheck-severity`, \
+         and warnings are reported without failing. Use `--no-shellcheck` to skip this \
+         check.",
+    ),
+    Rule::new(
+        "crlf-line-ending",
+        Severity::Warn,
+        "A build script has CRLF line endings",
+        "Carriage returns in `x_exec.run` or `x_exec.pkgver` end up in the commands and \
+         their arguments when the script runs. With `--fix` the line endings are converted \
+         to LF in the validated output.",
+    ),
+    Rule::new(
+        "mixed-indentation",
+        Severity::Warn,
+        "A build script mixes tabs and spaces for indentation",
+        "Mixed indentation makes heredocs using `<<-` and continued lines behave \
+         unexpectedly. With `--fix` the indentation is converted to spaces in the validated \
+         output, unless the script uses `<<-` heredocs.",
+    ),
+    Rule::new(
+        "line-too-long",
+        Severity::Warn,
+        "A line is longer than the configured limit",
+        "Only checked with `--max-line-length N`, which reports every line of the file \
+         longer than N characters, to keep diffs readable. Lines containing a URL are \
+         exempt, as URLs can't be wrapped. `--line-length-exempt` replaces the patterns of \
+         exempt lines, or exempts nothing when empty.",
+    ),
+    Rule::new(
+        "multiple-documents",
+        Severity::Error,
+        "The file contains more than one YAML document",
+        "An SBUILD file holds exactly one document. A `---` separator after the content, or \
+         content after a `...` end marker, usually means two files were concatenated by \
+         mistake. The line where the unexpected content starts is reported.",
+    ),
+    Rule::new(
+        "mixed-file-indentation",
+        Severity::Warn,
+        "The file mixes tabs and spaces for indentation",
+        "YAML is indented with spaces, so tabs at the start of a line can only come from \
+         the scripts, where they make the file hard to read and edit consistently. The \
+         first line indented differently from the ones before it is reported. With `--fix` \
+         the tabs in the scripts are converted to spaces in the validated output, unless a \
+         script uses `<<-` heredocs.",
+    ),
+    Rule::new(
+        "undefined-variable",
+        Severity::Warn,
+        "A build script uses a variable that is never set",
+        "The environment is cleared before `x_exec.run` and `x_exec.pkgver` run, so only the \
+         variables provided by sbuild (`pkg`, `pkg_id`, `pkg_type`, `pkgver`, `sbuild_outdir`, \
+         ... and their uppercase forms), those set by the shell and those assigned in the \
+         script are defined. Use `--allow-var` for variables provided otherwise.",
+    ),
+    Rule::new(
+        "missing-set-options",
+        Severity::Warn,
+        "`x_exec.run` doesn't enable the required shell options",
+        "With `--require-set`, `x_exec.run` must enable the given options (`errexit` \
+         by default) with `set` before its first command, e.g. `set -e` or `set -euo pipefail`, \
+         so a failing step stops the build instead of packaging its partial output. Options \
+         may be given by letter or by the name `set -o` takes.",
+    ),
+    Rule::new(
+        "unreachable-url",
+        Severity::Error,
+        "A URL is not reachable",
+        "With `--check-urls`, every `src_url` must respond successfully. Unreachable \
+         `homepage` URLs are reported as warnings.",
+    ),
+    Rule::new(
+        "non-html-homepage",
+        Severity::Warn,
+        "A homepage isn't served as a web page",
+        "With `--check-urls` and `--check-homepage-type`, a reachable `homepage` must be \
+         served as `text/html` (or `application/xhtml+xml`). Other types, e.g. \
+         `application/octet-stream`, usually mean the URL points at a download rather than \
+         the project's site. Homepages responding without a `Content-Type` are accepted.",
+    ),
+    Rule::new(
+        "duplicate-id",
+        Severity::Error,
+        "`pkg`, `pkg_id` or `app_id` is shared by several files",
+        "With `--check-unique`, the identifiers of every file that passed validation are \
+         compared once the batch is done. A collision usually means a recipe was copied \
+         without updating its identifiers.",
+    ),
+    Rule::new(
+        "inconsistent-field-shape",
+        Severity::Warn,
+        "A field is written in different shapes across the batch",
+        "With `--check-field-shapes`, the shape of every top-level field, a scalar, a list or \
+         a mapping, is compared across the files once the batch is done. The files using a \
+         less common shape than the others are reported, so tooling reading the repository \
+         can rely on one convention per field.",
+    ),
+    Rule::new(
+        "duplicate-config",
+        Severity::Warn,
+        "Several files have identical configs",
+        "With `--check-duplicates`, the configs of every file that passed validation are \
+         compared once normalized, i.e. as written in the validated output without comments, \
+         so copies differing only in formatting, quoting or comments are found too. Keep one \
+         of them.",
+    ),
+    Rule::new(
+        "pkgver",
+        Severity::Error,
+        "The package version could not be determined",
+        "In pkgver mode, `x_exec.pkgver` must run within `--timeout`, exit successfully \
+         without writing to stderr and print exactly one line.",
+    ),
+    Rule::new(
+        "unstable-pkgver",
+        Severity::Warn,
+        "The package version changes between runs",
+        "With `--pkgver-verify`, the `x_exec.pkgver` script is run a second time. A different \
+         result means the script resolves a moving target such as a `latest` release, which \
+         breaks reproducible builds.",
+    ),
+];
+
+pub fn find_rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
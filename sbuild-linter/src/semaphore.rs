@@ -13,17 +13,52 @@ impl Semaphore {
         }
     }
 
-    pub fn acquire(&self) {
+    // Waits for a permit, which is given back when the returned guard is
+    // dropped, including on early returns and panics.
+    pub fn acquire(&self) -> Permit<'_> {
         let mut permits = self.permits.lock().unwrap();
         while *permits == 0 {
             permits = self.condvar.wait(permits).unwrap();
         }
         *permits -= 1;
+        Permit { semaphore: self }
     }
 
-    pub fn release(&self) {
+    fn release(&self) {
         let mut permits = self.permits.lock().unwrap();
         *permits += 1;
         self.condvar.notify_one();
     }
 }
+
+pub struct Permit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::*;
+
+    #[test]
+    fn permits_are_given_back_when_dropped() {
+        let semaphore = Semaphore::new(1);
+        drop(semaphore.acquire());
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _permit = semaphore.acquire();
+            panic!("request failed");
+        }));
+        assert!(result.is_err());
+
+        let _permit = semaphore.acquire();
+        assert_eq!(*semaphore.permits.lock().unwrap(), 0);
+    }
+}
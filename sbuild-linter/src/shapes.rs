@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+
+use indexmap::IndexMap;
+use serde_yml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Shape {
+    Scalar,
+    List,
+    Mapping,
+}
+
+impl Shape {
+    pub fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Sequence(_) => Some(Shape::List),
+            Value::Mapping(_) => Some(Shape::Mapping),
+            Value::Tagged(tagged) => Shape::of(&tagged.value),
+            Value::Bool(_) | Value::Number(_) | Value::String(_) => Some(Shape::Scalar),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Shape::Scalar => "a scalar",
+            Shape::List => "a list",
+            Shape::Mapping => "a mapping",
+        }
+    }
+}
+
+// A field written in different shapes across the batch, with the files using
+// each shape, the most common shape first.
+pub struct Inconsistency {
+    pub field: String,
+    pub shapes: Vec<(Shape, Vec<String>)>,
+}
+
+// Shapes the top-level fields are written in across a batch, so tooling
+// reading the files can rely on a single convention per field.
+#[derive(Default)]
+pub struct FieldShapes {
+    // field -> shape -> files writing it that way
+    shapes: Mutex<IndexMap<String, IndexMap<Shape, Vec<String>>>>,
+}
+
+impl FieldShapes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, file_path: &str, field: &str, value: &Value) {
+        let Some(shape) = Shape::of(value) else {
+            return;
+        };
+        if let Ok(mut shapes) = self.shapes.lock() {
+            let files = shapes
+                .entry(field.to_string())
+                .or_default()
+                .entry(shape)
+                .or_default();
+            if !files.iter().any(|file| file == file_path) {
+                files.push(file_path.to_string());
+            }
+        }
+    }
+
+    // Returns every field written in more than one shape, sorted by field
+    // and with sorted files so the report doesn't depend on the order the jobs
+    // finished in.
+    pub fn inconsistencies(&self) -> Vec<Inconsistency> {
+        let Ok(shapes) = self.shapes.lock() else {
+            return Vec::new();
+        };
+
+        let mut inconsistencies: Vec<Inconsistency> = shapes
+            .iter()
+            .filter(|(_, by_shape)| by_shape.len() > 1)
+            .map(|(field, by_shape)| {
+                let mut shapes: Vec<(Shape, Vec<String>)> = by_shape
+                    .iter()
+                    .map(|(shape, files)| {
+                        let mut files = files.clone();
+                        files.sort();
+                        (*shape, files)
+                    })
+                    .collect();
+                shapes.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+                Inconsistency {
+                    field: field.clone(),
+                    shapes,
+                }
+            })
+            .collect();
+        inconsistencies.sort_by(|a, b| a.field.cmp(&b.field));
+        inconsistencies
+    }
+}
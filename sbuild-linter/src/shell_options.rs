@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+// Options with a single-letter flag, by the name `set -o` takes.
+const OPTION_LETTERS: [(char, &str); 7] = [
+    ('e', "errexit"),
+    ('E', "errtrace"),
+    ('f', "noglob"),
+    ('u', "nounset"),
+    ('v', "verbose"),
+    ('x', "xtrace"),
+    ('C', "noclobber"),
+];
+
+// Spells the option the way `set -o` does, so `e`, `-e` and `errexit` are the
+// same option.
+pub fn normalize(option: &str) -> String {
+    let option = option.trim().trim_start_matches('-');
+    let mut chars = option.chars();
+    if let (Some(letter), None) = (chars.next(), chars.next()) {
+        if let Some((_, name)) = OPTION_LETTERS.iter().find(|(l, _)| *l == letter) {
+            return name.to_string();
+        }
+    }
+    option.to_lowercase()
+}
+
+fn letter(name: &str) -> Option<char> {
+    OPTION_LETTERS
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(letter, _)| *letter)
+}
+
+// Options the script enables with `set` before its first command. Options set
+// later don't protect the commands before them, so they aren't counted.
+pub fn enabled_options(script: &str) -> HashSet<String> {
+    let mut enabled = HashSet::new();
+    for line in script.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for command in line.split(';') {
+            let mut words = command.split_whitespace();
+            match words.next() {
+                Some("set") => {}
+                None => continue,
+                _ => return enabled,
+            }
+            while let Some(word) = words.next() {
+                let (enable, flags) = match word.chars().next() {
+                    Some('-') => (true, &word[1..]),
+                    Some('+') => (false, &word[1..]),
+                    _ => break,
+                };
+                if flags.is_empty() || flags == "-" {
+                    break;
+                }
+                for flag in flags.chars() {
+                    let name = match flag {
+                        'o' => match words.next() {
+                            Some(name) => name.to_string(),
+                            None => continue,
+                        },
+                        flag => normalize(&flag.to_string()),
+                    };
+                    if enable {
+                        enabled.insert(name);
+                    } else {
+                        enabled.remove(&name);
+                    }
+                }
+            }
+        }
+    }
+    enabled
+}
+
+// Returns the required options the script doesn't enable at its top, spelled
+// as `set -o` takes them.
+pub fn missing(script: &str, required: &[String]) -> Vec<String> {
+    let enabled = enabled_options(script);
+    let mut missing: Vec<String> = Vec::new();
+    for option in required.iter().map(|option| normalize(option)) {
+        if !option.is_empty() && !enabled.contains(&option) && !missing.contains(&option) {
+            missing.push(option);
+        }
+    }
+    missing
+}
+
+// The `set` command enabling the options, e.g. `set -eu -o pipefail`.
+pub fn set_command(options: &[String]) -> String {
+    let letters: String = options.iter().filter_map(|name| letter(name)).collect();
+    let mut command = String::from("set");
+    if !letters.is_empty() {
+        command.push_str(&format!(" -{}", letters));
+    }
+    for name in options.iter().filter(|name| letter(name).is_none()) {
+        command.push_str(&format!(" -o {}", name));
+    }
+    command
+}
@@ -0,0 +1,145 @@
+use std::{io, process::Command, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{get_line_number_for_json_key, temp_script_file};
+
+// Severities of shellcheck findings, from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Style,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Style => "style",
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "style" => Ok(Level::Style),
+            "info" => Ok(Level::Info),
+            "warning" => Ok(Level::Warning),
+            "error" => Ok(Level::Error),
+            _ => Err(format!("Invalid shellcheck severity: '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub line: usize,
+    pub level: String,
+    pub code: u32,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+struct Output {
+    comments: Vec<Finding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outcome {
+    // whether shellcheck exited successfully, i.e. without findings at or
+    // above the severity
+    pub success: bool,
+    pub findings: Vec<Finding>,
+}
+
+impl Finding {
+    // Levels shellcheck doesn't document are treated as errors.
+    pub fn level(&self) -> Level {
+        self.level.parse().unwrap_or(Level::Error)
+    }
+}
+
+// Runs shellcheck on the script and returns the findings at or above the
+// given severity.
+pub fn run(script: &str, severity: Level) -> io::Result<Outcome> {
+    let tmp = temp_script_file(script);
+
+    let out = Command::new("shellcheck")
+        .arg(format!("--severity={}", severity.as_str()))
+        .arg("--format=json1")
+        .arg(tmp.path())
+        .output();
+
+    tmp.close()?;
+
+    let out = out?;
+    let findings = serde_json::from_slice::<Output>(&out.stdout)
+        .map(|output| output.comments)
+        .unwrap_or_default();
+    Ok(Outcome {
+        success: out.status.success(),
+        findings,
+    })
+}
+
+// Where an `x_exec` script is located in the source, so that lines reported
+// by shellcheck can be mapped back onto the SBUILD.
+pub struct ScriptLocation {
+    key_line: usize,
+    // first line of the script content when it is a block scalar (`|` or
+    // `>`), whose lines follow the source one-to-one
+    first_line: Option<usize>,
+}
+
+impl ScriptLocation {
+    pub fn find(source: &str, is_json: bool, key: &str) -> Self {
+        if is_json {
+            // the script is a single JSON string, so every line of it maps to
+            // the key
+            return ScriptLocation {
+                key_line: get_line_number_for_json_key(source, key),
+                first_line: None,
+            };
+        }
+
+        let needle = format!("{}:", key);
+        let mut in_x_exec = false;
+        for (index, line) in source.lines().enumerate() {
+            if !line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+                in_x_exec = line.trim_end().starts_with("x_exec:");
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            if in_x_exec && trimmed.starts_with(&needle) {
+                let value = trimmed[needle.len()..].trim_start();
+                let is_block = value.starts_with('|') || value.starts_with('>');
+                return ScriptLocation {
+                    key_line: index + 1,
+                    first_line: is_block.then_some(index + 2),
+                };
+            }
+        }
+
+        ScriptLocation {
+            key_line: 0,
+            first_line: None,
+        }
+    }
+
+    // Line 1 of the checked script is the shebang added by the linter, so
+    // the content starts at line 2.
+    pub fn source_line(&self, script_line: usize) -> usize {
+        match self.first_line {
+            Some(first_line) if script_line >= 2 => first_line + script_line - 2,
+            _ => self.key_line,
+        }
+    }
+}
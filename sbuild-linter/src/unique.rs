@@ -0,0 +1,62 @@
+use std::sync::Mutex;
+
+use indexmap::IndexMap;
+
+use crate::build_config::BuildConfig;
+
+pub struct Collision {
+    pub field: &'static str,
+    pub value: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct UniqueIds {
+    // (field, value) -> files defining it, in the order they were recorded
+    ids: Mutex<IndexMap<(&'static str, String), Vec<String>>>,
+}
+
+impl UniqueIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, file_path: &str, config: &BuildConfig) {
+        let mut entries = vec![("pkg", &config.pkg), ("pkg_id", &config.pkg_id)];
+        if let Some(ref app_id) = config.app_id {
+            entries.push(("app_id", app_id));
+        }
+
+        if let Ok(mut ids) = self.ids.lock() {
+            for (field, value) in entries {
+                ids.entry((field, value.clone()))
+                    .or_default()
+                    .push(file_path.to_string());
+            }
+        }
+    }
+
+    // Returns every identifier shared by more than one file, with the files
+    // sorted so the report doesn't depend on the order the jobs finished in.
+    pub fn collisions(&self) -> Vec<Collision> {
+        let Ok(ids) = self.ids.lock() else {
+            return Vec::new();
+        };
+
+        let mut collisions: Vec<Collision> = ids
+            .iter()
+            .filter(|(_, files)| files.len() > 1)
+            .map(|((field, value), files)| {
+                let mut files = files.clone();
+                files.sort();
+                Collision {
+                    field,
+                    value: value.clone(),
+                    files,
+                }
+            })
+            .collect();
+        collisions.sort_by(|a, b| (a.field, &a.value).cmp(&(b.field, &b.value)));
+        collisions
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use reqwest::{blocking::Client, header::CONTENT_TYPE, StatusCode};
+
+use crate::{semaphore::Semaphore, validator::url_host};
+
+pub struct UrlChecker {
+    client: Client,
+    global: Semaphore,
+    per_host: usize,
+    delay: Duration,
+    hosts: Mutex<HashMap<String, Arc<HostState>>>,
+}
+
+// What a URL responded with.
+pub struct UrlResponse {
+    pub status: StatusCode,
+    // the media type of the `Content-Type` header, without its parameters
+    pub content_type: Option<String>,
+}
+
+struct HostState {
+    semaphore: Semaphore,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl UrlChecker {
+    pub fn new(
+        global: usize,
+        per_host: usize,
+        delay: Duration,
+        timeout: Duration,
+    ) -> reqwest::Result<Self> {
+        let mut builder =
+            Client::builder().user_agent(format!("sbuild-linter/{}", env!("CARGO_PKG_VERSION")));
+        // requests are left without a timeout when it is zero
+        if !timeout.is_zero() {
+            builder = builder.timeout(timeout);
+        }
+        let client = builder.build()?;
+
+        Ok(UrlChecker {
+            client,
+            global: Semaphore::new(global),
+            per_host,
+            delay,
+            hosts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn host_state(&self, url: &str) -> Arc<HostState> {
+        // URLs without a host are all limited together
+        let host = url_host(url).unwrap_or_default();
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts
+            .entry(host)
+            .or_insert_with(|| {
+                Arc::new(HostState {
+                    semaphore: Semaphore::new(self.per_host),
+                    last_request: Mutex::new(None),
+                })
+            })
+            .clone()
+    }
+
+    // Sends a HEAD request (falling back to GET for servers that don't allow
+    // HEAD) while respecting the global and per-host concurrency limits and
+    // the delay between consecutive requests to the same host.
+    pub fn check(&self, url: &str) -> Result<UrlResponse, String> {
+        let host = self.host_state(url);
+
+        let _host_permit = host.semaphore.acquire();
+
+        // the delay is waited out before taking a global permit, so a
+        // throttled host doesn't hold up the checks of the other hosts
+        if !self.delay.is_zero() {
+            let mut last_request = host.last_request.lock().unwrap();
+            if let Some(last) = *last_request {
+                let elapsed = last.elapsed();
+                if elapsed < self.delay {
+                    thread::sleep(self.delay - elapsed);
+                }
+            }
+            *last_request = Some(Instant::now());
+        }
+
+        let _global_permit = self.global.acquire();
+        self.client
+            .head(url)
+            .send()
+            .and_then(|resp| {
+                if resp.status() == StatusCode::METHOD_NOT_ALLOWED {
+                    self.client.get(url).send()
+                } else {
+                    Ok(resp)
+                }
+            })
+            .map(|resp| UrlResponse {
+                status: resp.status(),
+                content_type: resp
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.split(';').next())
+                    .map(|media_type| media_type.trim().to_ascii_lowercase()),
+            })
+            .map_err(|err| err.to_string())
+    }
+}
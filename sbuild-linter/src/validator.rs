@@ -1,16 +1,19 @@
 use std::collections::HashSet;
 
+use serde_json::json;
 use serde_yml::{Mapping, Value};
 
 use crate::{
     build_config::visitor::BuildConfigVisitor, disabled::ComplexReason, error::Severity,
-    VALID_ARCH, VALID_CATEGORIES, VALID_OS,
+    FORMAT_VERSION, MIN_FORMAT_VERSION, VALID_ARCH, VALID_CATEGORIES, VALID_OS,
 };
 
 pub enum FieldType {
     Boolean,
+    FormatVersion,
     String,
     StringArray,
+    UrlArray,
     BuildAsset,
     DistroPkg,
     XExec,
@@ -21,6 +24,79 @@ pub enum FieldType {
     DisabledReason,
 }
 
+impl FieldType {
+    fn schema(&self) -> serde_json::Value {
+        let string_array = json!({ "type": "array", "items": { "type": "string" } });
+        let url = json!({ "type": "string", "format": "uri" });
+
+        match self {
+            FieldType::Boolean => json!({ "type": "boolean" }),
+            FieldType::FormatVersion => json!({ "type": "integer", "minimum": 1 }),
+            FieldType::String => json!({ "type": "string" }),
+            FieldType::StringArray => string_array,
+            FieldType::UrlArray => json!({ "type": "array", "items": url }),
+            FieldType::Url => url,
+            FieldType::BuildAsset => json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": { "url": url, "out": { "type": "string" } },
+                    "required": ["url", "out"]
+                }
+            }),
+            FieldType::DistroPkg => json!({ "type": "object" }),
+            FieldType::XExec => json!({
+                "type": "object",
+                "properties": {
+                    "arch": { "type": "array", "items": { "enum": VALID_ARCH } },
+                    "os": { "type": "array", "items": { "enum": VALID_OS } },
+                    "host": string_array,
+                    "conflicts": string_array,
+                    "depends": string_array,
+                    "entrypoint": { "type": "string" },
+                    "pkgver": { "type": "string" },
+                    "shell": { "type": "string" },
+                    "run": { "type": "string" }
+                },
+                "required": ["shell", "run"]
+            }),
+            FieldType::Description => json!({
+                "oneOf": [
+                    { "type": "string" },
+                    { "type": "object", "additionalProperties": { "type": "string" } }
+                ]
+            }),
+            FieldType::Resource => json!({
+                "type": "object",
+                "properties": {
+                    "url": url,
+                    "file": { "type": "string" },
+                    "dir": { "type": "string" }
+                }
+            }),
+            FieldType::License => json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "url": url,
+                        "file": { "type": "string" }
+                    },
+                    "required": ["id"]
+                }
+            }),
+            FieldType::DisabledReason => json!({
+                "oneOf": [
+                    { "type": "string" },
+                    string_array,
+                    { "type": "object", "maxProperties": 1 }
+                ]
+            }),
+        }
+    }
+}
+
 pub struct FieldValidator {
     pub name: &'static str,
     field_type: FieldType,
@@ -45,8 +121,9 @@ impl FieldValidator {
     ) -> Option<Value> {
         match &self.field_type {
             FieldType::Boolean => self.validate_boolean(value, visitor, line_number),
+            FieldType::FormatVersion => self.validate_format_version(value, visitor, line_number),
             FieldType::String => self.validate_string(value, visitor, line_number, required),
-            FieldType::StringArray => {
+            FieldType::StringArray | FieldType::UrlArray => {
                 self.validate_string_array(value, visitor, line_number, required)
             }
             FieldType::BuildAsset => self.validate_build_asset(value, visitor, line_number),
@@ -66,17 +143,113 @@ impl FieldValidator {
         visitor: &mut BuildConfigVisitor,
         line_number: usize,
     ) -> Option<Value> {
-        if value.is_bool() {
-            Some(value.clone())
-        } else {
+        match value {
+            Value::Bool(b) => {
+                // `True`, `FALSE` and friends are still booleans in YAML, but
+                // only the lowercase spelling is used across the repository
+                if let Some(raw) = visitor.raw_scalar(line_number) {
+                    if raw != "true" && raw != "false" {
+                        visitor.record_error(
+                            "non-canonical-boolean",
+                            self.name.to_string(),
+                            format!("'{}' should be spelled `{}`, found '{}'", self.name, b, raw),
+                            line_number,
+                            Severity::Warn,
+                        );
+                    }
+                }
+                Some(value.clone())
+            }
+            Value::String(s) if parse_boolish(s).is_some() => {
+                let b = parse_boolish(s).unwrap_or_default();
+                visitor.record_error(
+                    "non-canonical-boolean",
+                    self.name.to_string(),
+                    format!(
+                        "'{}' field must be a boolean, found the string '{}'. Use `{}` instead",
+                        self.name, s, b
+                    ),
+                    line_number,
+                    Severity::Warn,
+                );
+                Some(Value::Bool(b))
+            }
+            _ => {
+                visitor.record_error(
+                    "invalid-type",
+                    self.name.to_string(),
+                    format!("'{}' field must be a boolean", self.name),
+                    line_number,
+                    Severity::Error,
+                );
+                None
+            }
+        }
+    }
+
+    // Newer versions may have fields and rules this linter doesn't know, so
+    // they are validated as the current one with a warning. Versions older
+    // than the oldest supported one are interpreted differently and rejected.
+    fn validate_format_version(
+        &self,
+        value: &Value,
+        visitor: &mut BuildConfigVisitor,
+        line_number: usize,
+    ) -> Option<Value> {
+        // like quoted booleans, quoted numbers are reported and converted
+        let value = match value {
+            Value::String(s) if s.trim().parse::<u64>().is_ok() => {
+                let number = s.trim().parse::<u64>().unwrap_or_default();
+                visitor.record_error(
+                    "quoted-number",
+                    self.name.to_string(),
+                    format!(
+                        "'{}' field must be a number, found the string '{}'. Use `{}` instead",
+                        self.name, s, number
+                    ),
+                    line_number,
+                    Severity::Warn,
+                );
+                &Value::Number(number.into())
+            }
+            value => value,
+        };
+        let Some(version) = value.as_u64().filter(|version| *version > 0) else {
             visitor.record_error(
+                "invalid-type",
                 self.name.to_string(),
-                format!("'{}' field must be a boolean", self.name),
+                format!("'{}' field must be a positive integer", self.name),
                 line_number,
                 Severity::Error,
             );
-            None
+            return None;
+        };
+
+        if version > FORMAT_VERSION {
+            visitor.record_error(
+                "unsupported-format-version",
+                self.name.to_string(),
+                format!(
+                    "Format version {} is newer than the supported version {}. Some fields may not be validated",
+                    version, FORMAT_VERSION
+                ),
+                line_number,
+                Severity::Warn,
+            );
+        } else if version < MIN_FORMAT_VERSION {
+            visitor.record_error(
+                "unsupported-format-version",
+                self.name.to_string(),
+                format!(
+                    "Format version {} is no longer supported. Update the file to version {}",
+                    version, FORMAT_VERSION
+                ),
+                line_number,
+                Severity::Error,
+            );
+            return None;
         }
+        Some(value.clone())
     }
 
     fn validate_string(
@@ -90,6 +263,7 @@ impl FieldValidator {
             if s.trim().is_empty() {
                 if required {
                     visitor.record_error(
+                        "empty-value",
                         self.name.to_string(),
                         format!("'{}' field cannot be empty", self.name),
                         line_number,
@@ -103,6 +277,7 @@ impl FieldValidator {
         } else {
             if required {
                 visitor.record_error(
+                    "invalid-type",
                     self.name.to_string(),
                     format!("'{}' field must be a string", self.name),
                     line_number,
@@ -125,11 +300,11 @@ impl FieldValidator {
             if is_valid_url(&v) {
                 Some(Value::String(v))
             } else {
-                visitor.record_error(
+                visitor.record_invalid_url(
                     self.name.to_string(),
+                    &v,
                     format!("'{}' field must be a valid URL", self.name),
                     line_number,
-                    Severity::Error,
                 );
                 None
             }
@@ -147,36 +322,60 @@ impl FieldValidator {
     ) -> Option<Value> {
         if let Some(arr) = value.as_sequence() {
             let mut seen = HashSet::new();
+            // entries dropped for being blank or not strings, as opposed to
+            // duplicates
+            let mut rejected = 0;
             let valid_strings: Vec<String> = arr
                 .iter()
-                .filter_map(|v| {
-                    if let Some(s) = v.as_str() {
-                        if !s.trim().is_empty() {
-                            Some(s.to_string())
-                        } else {
-                            None
-                        }
+                .enumerate()
+                .filter_map(|(index, v)| {
+                    // a bare `-` is a null entry
+                    if v.is_null() || v.as_str().is_some_and(|s| s.trim().is_empty()) {
+                        rejected += 1;
+                        visitor.record_error(
+                            "empty-value",
+                            self.name.to_string(),
+                            format!(
+                                "'{}' field has a blank entry at item {}. Remove it or fill it in",
+                                self.name,
+                                index + 1
+                            ),
+                            line_number,
+                            Severity::Error,
+                        );
+                        None
+                    } else if let Some(s) = v.as_str() {
+                        Some(s.to_string())
                     } else {
-                        if !v.is_null() {
-                            visitor.record_error(
-                                self.name.to_string(),
-                                format!(
-                                    "'{}' field must only contain sequence of strings",
-                                    self.name
-                                ),
-                                line_number,
-                                Severity::Error,
-                            );
-                        }
+                        rejected += 1;
+                        visitor.record_error(
+                            "invalid-type",
+                            self.name.to_string(),
+                            format!(
+                                "'{}' field must only contain sequence of strings",
+                                self.name
+                            ),
+                            line_number,
+                            Severity::Error,
+                        );
                         None
                     }
                 })
-                .filter(|s| seen.insert(s.clone()))
+                .filter(|s| {
+                    // URLs differing only in the case of scheme or host are
+                    // still duplicates
+                    if matches!(self.field_type, FieldType::UrlArray) {
+                        seen.insert(normalize_url(s))
+                    } else {
+                        seen.insert(s.clone())
+                    }
+                })
                 .collect();
 
             if valid_strings.is_empty() {
                 if required {
                     visitor.record_error(
+                        "empty-value",
                         self.name.to_string(),
                         format!(
                             "'{}' field must contain at least 1 non-empty string",
@@ -188,8 +387,9 @@ impl FieldValidator {
                 }
                 None
             } else {
-                if valid_strings.len() != arr.len() {
+                if valid_strings.len() + rejected != arr.len() {
                     visitor.record_error(
+                        "duplicate-value",
                         self.name.to_string(),
                         format!(
                             "'{}' field contains duplicates. Removed automatically..",
@@ -206,6 +406,7 @@ impl FieldValidator {
         } else {
             if required {
                 visitor.record_error(
+                    "invalid-type",
                     self.name.to_string(),
                     format!("'{}' field must be an array", self.name),
                     line_number,
@@ -226,6 +427,7 @@ impl FieldValidator {
             Some(value.clone())
         } else {
             visitor.record_error(
+                "invalid-type",
                 self.name.to_string(),
                 format!("'{}' field must be an object", self.name),
                 line_number,
@@ -245,6 +447,7 @@ impl FieldValidator {
             Value::String(s) => {
                 if s.trim().is_empty() {
                     visitor.record_error(
+                        "empty-value",
                         self.name.to_string(),
                         format!("'{}' field cannot be empty", self.name),
                         line_number,
@@ -261,6 +464,7 @@ impl FieldValidator {
 
                 if map.is_empty() {
                     visitor.record_error(
+                        "empty-value",
                         self.name.to_string(),
                         format!("'{}' field cannot be empty", self.name),
                         line_number,
@@ -279,6 +483,7 @@ impl FieldValidator {
                                 );
                             } else {
                                 visitor.record_error(
+                                    "empty-value",
                                     format!("{}.{}", self.name, key_str),
                                     "Description value cannot be empty".to_string(),
                                     line_number,
@@ -288,6 +493,7 @@ impl FieldValidator {
                             }
                         } else {
                             visitor.record_error(
+                                "invalid-type",
                                 format!("{}.{}", self.name, key_str),
                                 "Description value must be a string".to_string(),
                                 line_number,
@@ -297,6 +503,7 @@ impl FieldValidator {
                         }
                     } else {
                         visitor.record_error(
+                            "invalid-type",
                             self.name.to_string(),
                             "Package name must be a string".to_string(),
                             line_number,
@@ -314,6 +521,7 @@ impl FieldValidator {
             }
             _ => {
                 visitor.record_error(
+                    "invalid-type",
                     self.name.to_string(),
                     format!(
                         "'{}' field must be either a string or a mapping of strings",
@@ -342,6 +550,7 @@ impl FieldValidator {
 
                 if map.len() != 1 {
                     visitor.record_error(
+                        "invalid-type",
                         self.name.to_string(),
                         "'{}' field must contain exactly one key".to_string(),
                         line_number,
@@ -372,6 +581,7 @@ impl FieldValidator {
                                                                 inner_val_str.to_string();
                                                         } else {
                                                             visitor.record_error(
+                                                                "empty-value",
                                                                 format!(
                                                                     "{}.{}",
                                                                     self.name, key_str
@@ -384,6 +594,7 @@ impl FieldValidator {
                                                         }
                                                     } else {
                                                         visitor.record_error(
+                                                            "invalid-type",
                                                             format!("{}.{}", self.name, key_str),
                                                             "Date must be a string".to_string(),
                                                             line_number,
@@ -407,6 +618,7 @@ impl FieldValidator {
                                                                 inner_val_str.to_string();
                                                         } else {
                                                             visitor.record_error(
+                                                                "empty-value",
                                                                 format!(
                                                                     "{}.{}",
                                                                     self.name, key_str
@@ -420,6 +632,7 @@ impl FieldValidator {
                                                         }
                                                     } else {
                                                         visitor.record_error(
+                                                            "invalid-type",
                                                             format!("{}.{}", self.name, key_str),
                                                             "Reason must be a string".to_string(),
                                                             line_number,
@@ -430,6 +643,7 @@ impl FieldValidator {
                                                 }
                                                 _ => {
                                                     visitor.record_error(
+                                                        "unknown-field",
                                                         format!("{}.{}", self.name, key_str),
                                                         "Invalid key".to_string(),
                                                         line_number,
@@ -440,6 +654,7 @@ impl FieldValidator {
                                             }
                                         } else {
                                             visitor.record_error(
+                                                "invalid-type",
                                                 format!("{}.{}", self.name, key_str),
                                                 "Key must be a string".to_string(),
                                                 line_number,
@@ -457,6 +672,7 @@ impl FieldValidator {
                                     }
                                 } else {
                                     visitor.record_error(
+                                        "invalid-type",
                                         format!("{}.{}", self.name, key_str),
                                         "Value must be a mapping with disabled `date` and `reason`"
                                             .to_string(),
@@ -469,6 +685,7 @@ impl FieldValidator {
                         }
                     } else {
                         visitor.record_error(
+                            "invalid-type",
                             self.name.to_string(),
                             "Package name must be a string".to_string(),
                             line_number,
@@ -486,6 +703,7 @@ impl FieldValidator {
             }
             _ => {
                 visitor.record_error(
+                    "invalid-type",
                     self.name.to_string(),
                     format!(
                         "'{}' field must be either a string, sequence, or a mapping with `date` and `reason`",
@@ -517,11 +735,11 @@ impl FieldValidator {
                             if let Some(url_str) = url.as_str() {
                                 if !url_str.trim().is_empty() {
                                     if !is_valid_url(url_str) {
-                                        visitor.record_error(
+                                        visitor.record_invalid_url(
                                             "build_asset.url".to_string(),
+                                            url_str,
                                             format!("'{}' is not a valid URL.", url_str),
                                             line_number,
-                                            Severity::Error,
                                         );
                                         valid = false;
                                     } else {
@@ -532,6 +750,7 @@ impl FieldValidator {
                                     }
                                 } else {
                                     visitor.record_error(
+                                        "empty-value",
                                         "build_asset.url".to_string(),
                                         "URL cannot be empty".to_string(),
                                         line_number,
@@ -541,6 +760,7 @@ impl FieldValidator {
                                 }
                             } else {
                                 visitor.record_error(
+                                    "invalid-type",
                                     "build_asset.url".to_string(),
                                     "URL must be a string".to_string(),
                                     line_number,
@@ -550,6 +770,7 @@ impl FieldValidator {
                             }
                         } else {
                             visitor.record_error(
+                                "missing-field",
                                 "build_asset".to_string(),
                                 "Missing required 'url' field".to_string(),
                                 line_number,
@@ -567,6 +788,7 @@ impl FieldValidator {
                                     );
                                 } else {
                                     visitor.record_error(
+                                        "empty-value",
                                         "build_asset.out".to_string(),
                                         "'out' field cannot be empty".to_string(),
                                         line_number,
@@ -576,6 +798,7 @@ impl FieldValidator {
                                 }
                             } else {
                                 visitor.record_error(
+                                    "invalid-type",
                                     "build_asset.out".to_string(),
                                     "'out' field must be a string".to_string(),
                                     line_number,
@@ -585,6 +808,7 @@ impl FieldValidator {
                             }
                         } else {
                             visitor.record_error(
+                                "missing-field",
                                 "build_asset".to_string(),
                                 "Missing required 'out' field".to_string(),
                                 line_number,
@@ -600,6 +824,7 @@ impl FieldValidator {
                         }
                     } else {
                         visitor.record_error(
+                            "invalid-type",
                             "build_asset".to_string(),
                             "Each build asset must be an object".to_string(),
                             line_number,
@@ -614,6 +839,7 @@ impl FieldValidator {
                 Some(Value::Sequence(validated_assets))
             } else {
                 visitor.record_error(
+                    "empty-value",
                     "build_asset".to_string(),
                     "No valid build assets found".to_string(),
                     line_number,
@@ -623,6 +849,7 @@ impl FieldValidator {
             }
         } else {
             visitor.record_error(
+                "invalid-type",
                 "build_asset".to_string(),
                 "Must be an array of build assets".to_string(),
                 line_number,
@@ -655,6 +882,7 @@ impl FieldValidator {
                                     );
                                 } else {
                                     visitor.record_error(
+                                        "empty-value",
                                         format!("{}.url", &self.name),
                                         "License id cannot be empty".to_string(),
                                         line_number,
@@ -664,6 +892,7 @@ impl FieldValidator {
                                 }
                             } else {
                                 visitor.record_error(
+                                    "invalid-type",
                                     format!("{}.id", &self.name),
                                     "License id must be a string".to_string(),
                                     line_number,
@@ -673,6 +902,7 @@ impl FieldValidator {
                             }
                         } else {
                             visitor.record_error(
+                                "missing-field",
                                 format!("{}.id", &self.name),
                                 "License id is required".to_string(),
                                 line_number,
@@ -685,11 +915,11 @@ impl FieldValidator {
                             if let Some(url_str) = url.as_str() {
                                 if !url_str.trim().is_empty() {
                                     if !is_valid_url(url_str) {
-                                        visitor.record_error(
+                                        visitor.record_invalid_url(
                                             format!("{}.url", &self.name),
+                                            url_str,
                                             format!("'{}' is not a valid URL.", url_str),
                                             line_number,
-                                            Severity::Error,
                                         );
                                         valid = false;
                                     } else {
@@ -700,6 +930,7 @@ impl FieldValidator {
                                     }
                                 } else {
                                     visitor.record_error(
+                                        "empty-value",
                                         format!("{}.url", &self.name),
                                         "URL cannot be empty".to_string(),
                                         line_number,
@@ -709,6 +940,7 @@ impl FieldValidator {
                                 }
                             } else {
                                 visitor.record_error(
+                                    "invalid-type",
                                     format!("{}.url", &self.name),
                                     "URL must be a string".to_string(),
                                     line_number,
@@ -727,6 +959,7 @@ impl FieldValidator {
                                     );
                                 } else {
                                     visitor.record_error(
+                                        "empty-value",
                                         format!("{}.file", &self.name),
                                         "'file' field cannot be empty".to_string(),
                                         line_number,
@@ -736,6 +969,7 @@ impl FieldValidator {
                                 }
                             } else {
                                 visitor.record_error(
+                                    "invalid-type",
                                     format!("{}.file", &self.name),
                                     "'file' field must be a string".to_string(),
                                     line_number,
@@ -755,6 +989,7 @@ impl FieldValidator {
                             Some(Value::String(v_str.to_string()))
                         } else {
                             visitor.record_error(
+                                "empty-value",
                                 self.name.to_string(),
                                 "'license' cannot be empty".to_string(),
                                 line_number,
@@ -762,7 +997,24 @@ impl FieldValidator {
                             );
                             None
                         }
+                    } else if license.is_null() {
+                        // a bare `-`
+                        visitor.record_error(
+                            "empty-value",
+                            self.name.to_string(),
+                            "'license' cannot have blank entries".to_string(),
+                            line_number,
+                            Severity::Error,
+                        );
+                        None
                     } else {
+                        visitor.record_error(
+                            "invalid-type",
+                            self.name.to_string(),
+                            "Each license must be a string or an object".to_string(),
+                            line_number,
+                            Severity::Error,
+                        );
                         None
                     }
                 })
@@ -772,6 +1024,7 @@ impl FieldValidator {
                 Some(Value::Sequence(validated_licenses))
             } else {
                 visitor.record_error(
+                    "empty-value",
                     "license".to_string(),
                     "No valid license found".to_string(),
                     line_number,
@@ -781,6 +1034,7 @@ impl FieldValidator {
             }
         } else {
             visitor.record_error(
+                "invalid-type",
                 "build_asset".to_string(),
                 "Must be an array of build assets".to_string(),
                 line_number,
@@ -804,11 +1058,11 @@ impl FieldValidator {
                 if let Some(url_str) = url.as_str() {
                     if !url_str.trim().is_empty() {
                         if !is_valid_url(url_str) {
-                            visitor.record_error(
+                            visitor.record_invalid_url(
                                 format!("{}.url", &self.name),
+                                url_str,
                                 format!("'{}' is not a valid URL.", url_str),
                                 line_number,
-                                Severity::Error,
                             );
                             valid = false;
                         } else {
@@ -819,6 +1073,7 @@ impl FieldValidator {
                         }
                     } else {
                         visitor.record_error(
+                            "empty-value",
                             format!("{}.url", &self.name),
                             "URL cannot be empty".to_string(),
                             line_number,
@@ -828,6 +1083,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         format!("{}.url", &self.name),
                         "URL must be a string".to_string(),
                         line_number,
@@ -846,6 +1102,7 @@ impl FieldValidator {
                         );
                     } else {
                         visitor.record_error(
+                            "empty-value",
                             format!("{}.file", &self.name),
                             "'file' field cannot be empty".to_string(),
                             line_number,
@@ -855,6 +1112,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         format!("{}.file", &self.name),
                         "'file' field must be a string".to_string(),
                         line_number,
@@ -873,6 +1131,7 @@ impl FieldValidator {
                         );
                     } else {
                         visitor.record_error(
+                            "empty-value",
                             format!("{}.dir", &self.name),
                             "'dir' field cannot be empty".to_string(),
                             line_number,
@@ -882,6 +1141,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         format!("{}.dir", &self.name),
                         "'dir' field must be a string".to_string(),
                         line_number,
@@ -894,6 +1154,7 @@ impl FieldValidator {
             if valid {
                 if validated_map.is_empty() {
                     visitor.record_error(
+                        "missing-field",
                         self.name.to_string(),
                         "Must contain atleast one of `url`, `file` or `dir`".to_string(),
                         line_number,
@@ -908,7 +1169,8 @@ impl FieldValidator {
             }
         } else {
             visitor.record_error(
-                format!("{}", &self.name),
+                "missing-field",
+                self.name.to_string(),
                 "Must contain atleast one of `url`, `file` or `dir`".to_string(),
                 line_number,
                 Severity::Error,
@@ -937,6 +1199,7 @@ impl FieldValidator {
                             );
                         } else {
                             visitor.record_error(
+                                "missing-shell",
                                 "x_exec.shell".to_string(),
                                 format!("{} is not installed.", shell_str),
                                 line_number,
@@ -945,6 +1208,7 @@ impl FieldValidator {
                         }
                     } else {
                         visitor.record_error(
+                            "empty-value",
                             "x_exec.shell".to_string(),
                             "Shell cannot be empty".to_string(),
                             line_number,
@@ -954,6 +1218,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.shell".to_string(),
                         "Shell must be a string".to_string(),
                         line_number,
@@ -963,6 +1228,7 @@ impl FieldValidator {
                 }
             } else {
                 visitor.record_error(
+                    "missing-field",
                     "x_exec".to_string(),
                     "Missing required 'shell' field".to_string(),
                     line_number,
@@ -980,6 +1246,7 @@ impl FieldValidator {
                         );
                     } else {
                         visitor.record_error(
+                            "empty-value",
                             "x_exec.run".to_string(),
                             "'run' field cannot be empty".to_string(),
                             line_number,
@@ -989,6 +1256,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.run".to_string(),
                         "'run' field must be a string".to_string(),
                         line_number,
@@ -998,6 +1266,7 @@ impl FieldValidator {
                 }
             } else {
                 visitor.record_error(
+                    "missing-field",
                     "x_exec".to_string(),
                     "Missing required 'run' field".to_string(),
                     line_number,
@@ -1014,6 +1283,7 @@ impl FieldValidator {
                     );
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.pkgver".to_string(),
                         "'pkgver' must be a string".to_string(),
                         line_number,
@@ -1031,6 +1301,7 @@ impl FieldValidator {
                     );
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.entrypoint".to_string(),
                         "'entrypoint' must be a string".to_string(),
                         line_number,
@@ -1055,6 +1326,7 @@ impl FieldValidator {
                             } else {
                                 if !v.is_null() {
                                     visitor.record_error(
+                                        "invalid-type",
                                         "x_exec.arch".to_string(),
                                         format!(
                                             "'{}.arch' must only contain sequence of strings",
@@ -1073,6 +1345,7 @@ impl FieldValidator {
                     for s in &valid_strings {
                         if !VALID_ARCH.contains(&s.as_str()) {
                             visitor.record_error(
+                                "invalid-platform",
                                 "x_exec.arch".to_string(),
                                 format!("'{}' is not a supported architecture.", s),
                                 line_number,
@@ -1085,6 +1358,7 @@ impl FieldValidator {
                     if valid {
                         if valid_strings.len() != arr.len() {
                             visitor.record_error(
+                                "duplicate-value",
                                 self.name.to_string(),
                                 format!(
                                     "'{}.arch' field contains duplicates. Removed automatically..",
@@ -1101,6 +1375,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.arch".to_string(),
                         format!("'{}.arch' must be an array of strings", self.name),
                         line_number,
@@ -1125,6 +1400,7 @@ impl FieldValidator {
                             } else {
                                 if !v.is_null() {
                                     visitor.record_error(
+                                        "invalid-type",
                                         "x_exec.os".to_string(),
                                         format!(
                                             "'{}.os' must only contain sequence of strings",
@@ -1143,6 +1419,7 @@ impl FieldValidator {
                     for s in &valid_strings {
                         if !VALID_OS.contains(&s.as_str()) {
                             visitor.record_error(
+                                "invalid-platform",
                                 "x_exec.os".to_string(),
                                 format!("'{}' is not a supported OS.", s),
                                 line_number,
@@ -1155,6 +1432,7 @@ impl FieldValidator {
                     if valid {
                         if valid_strings.len() != arr.len() {
                             visitor.record_error(
+                                "duplicate-value",
                                 self.name.to_string(),
                                 format!(
                                     "'{}.os' contains duplicates. Removed automatically..",
@@ -1171,6 +1449,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.os".to_string(),
                         format!("'{}.os' must be an array of strings", self.name),
                         line_number,
@@ -1195,6 +1474,7 @@ impl FieldValidator {
                             } else {
                                 if !v.is_null() {
                                     visitor.record_error(
+                                        "invalid-type",
                                         "x_exec.host".to_string(),
                                         format!(
                                             "'{}.host' must only contain sequence of strings",
@@ -1217,6 +1497,7 @@ impl FieldValidator {
                             && VALID_OS.contains(&parts[1]))
                         {
                             visitor.record_error(
+                                "invalid-platform",
                                 "x_exec.host".to_string(),
                                 format!("'{}' is not a supported `arch-os` combination.", s),
                                 line_number,
@@ -1229,6 +1510,7 @@ impl FieldValidator {
                     if valid {
                         if valid_strings.len() != arr.len() {
                             visitor.record_error(
+                                "duplicate-value",
                                 self.name.to_string(),
                                 format!(
                                     "'{}.host' field contains duplicates. Removed automatically..",
@@ -1245,6 +1527,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.host".to_string(),
                         format!("'{}.host' must be an array of strings", self.name),
                         line_number,
@@ -1269,6 +1552,7 @@ impl FieldValidator {
                             } else {
                                 if !v.is_null() {
                                     visitor.record_error(
+                                        "invalid-type",
                                         "x_exec.conflicts".to_string(),
                                         format!(
                                             "'{}.conflicts' must only contain sequence of strings",
@@ -1287,6 +1571,7 @@ impl FieldValidator {
                     if valid {
                         if valid_strings.len() != arr.len() {
                             visitor.record_error(
+                                "duplicate-value",
                                 self.name.to_string(),
                                 format!(
                                     "'{}.conflicts' contains duplicates. Removed automatically..",
@@ -1303,6 +1588,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.conflicts".to_string(),
                         format!("'{}.conflicts' must be an array of strings", self.name),
                         line_number,
@@ -1327,6 +1613,7 @@ impl FieldValidator {
                             } else {
                                 if !v.is_null() {
                                     visitor.record_error(
+                                        "invalid-type",
                                         "x_exec.depends".to_string(),
                                         format!(
                                             "'{}.depends' must only contain sequence of strings",
@@ -1345,6 +1632,7 @@ impl FieldValidator {
                     if valid {
                         if valid_strings.len() != arr.len() {
                             visitor.record_error(
+                                "duplicate-value",
                                 self.name.to_string(),
                                 format!(
                                     "'{}.depends' contains duplicates. Removed automatically..",
@@ -1361,6 +1649,7 @@ impl FieldValidator {
                     }
                 } else {
                     visitor.record_error(
+                        "invalid-type",
                         "x_exec.depends".to_string(),
                         format!("'{}.depends' must be an array of strings", self.name),
                         line_number,
@@ -1377,6 +1666,7 @@ impl FieldValidator {
             }
         } else {
             visitor.record_error(
+                "invalid-type",
                 "x_exec".to_string(),
                 "Must be an object".to_string(),
                 line_number,
@@ -1388,6 +1678,7 @@ impl FieldValidator {
 }
 
 pub const FIELD_VALIDATORS: &[FieldValidator] = &[
+    FieldValidator::new("format_version", FieldType::FormatVersion, false),
     FieldValidator::new("_disabled", FieldType::Boolean, true),
     FieldValidator::new("_disabled_reason", FieldType::DisabledReason, false),
     FieldValidator::new("pkg", FieldType::String, true),
@@ -1400,7 +1691,7 @@ pub const FIELD_VALIDATORS: &[FieldValidator] = &[
     FieldValidator::new("category", FieldType::StringArray, false),
     FieldValidator::new("description", FieldType::Description, true),
     FieldValidator::new("distro_pkg", FieldType::DistroPkg, false),
-    FieldValidator::new("homepage", FieldType::StringArray, false),
+    FieldValidator::new("homepage", FieldType::UrlArray, false),
     FieldValidator::new("maintainer", FieldType::StringArray, false),
     FieldValidator::new("icon", FieldType::Resource, false),
     FieldValidator::new("desktop", FieldType::Resource, false),
@@ -1408,19 +1699,249 @@ pub const FIELD_VALIDATORS: &[FieldValidator] = &[
     FieldValidator::new("note", FieldType::StringArray, false),
     FieldValidator::new("provides", FieldType::StringArray, false),
     FieldValidator::new("repology", FieldType::StringArray, false),
-    FieldValidator::new("src_url", FieldType::StringArray, true),
+    FieldValidator::new("src_url", FieldType::UrlArray, true),
     FieldValidator::new("tag", FieldType::StringArray, false),
     FieldValidator::new("x_exec", FieldType::XExec, true),
 ];
 
+// Fields a desktop image format needs for menu integration. The icon isn't
+// listed as the builder extracts it from the image when it isn't given.
+pub const GUI_REQUIRED_FIELDS: &[&str] = &["app_id"];
+
+// Fields that become required depending on `pkg_type`.
+pub const PKG_TYPE_REQUIRED_FIELDS: &[(&str, &[&str])] = &[
+    ("appbundle", GUI_REQUIRED_FIELDS),
+    ("appimage", GUI_REQUIRED_FIELDS),
+    ("flatimage", GUI_REQUIRED_FIELDS),
+    ("gameimage", GUI_REQUIRED_FIELDS),
+    ("nixappimage", GUI_REQUIRED_FIELDS),
+    ("runimage", GUI_REQUIRED_FIELDS),
+];
+
+// Categories and the more general one they belong to, which is redundant
+// next to them. Only categories with a single obvious parent are listed.
+pub const CATEGORY_PARENTS: &[(&str, &str)] = &[
+    ("ActionGame", "Game"),
+    ("AdventureGame", "Game"),
+    ("ArcadeGame", "Game"),
+    ("BlocksGame", "Game"),
+    ("BoardGame", "Game"),
+    ("CardGame", "Game"),
+    ("KidsGame", "Game"),
+    ("LogicGame", "Game"),
+    ("RolePlaying", "Game"),
+    ("Shooter", "Game"),
+    ("SportsGame", "Game"),
+    ("StrategyGame", "Game"),
+    ("Audio", "AudioVideo"),
+    ("Video", "AudioVideo"),
+    ("AudioVideoEditing", "AudioVideo"),
+    ("Debugger", "Development"),
+    ("GUIDesigner", "Development"),
+    ("IDE", "Development"),
+    ("Profiling", "Development"),
+    ("RevisionControl", "Development"),
+    ("2DGraphics", "Graphics"),
+    ("3DGraphics", "Graphics"),
+    ("RasterGraphics", "Graphics"),
+    ("VectorGraphics", "Graphics"),
+    ("Chat", "Network"),
+    ("Email", "Network"),
+    ("FileTransfer", "Network"),
+    ("IRCClient", "Network"),
+    ("InstantMessaging", "Network"),
+    ("P2P", "Network"),
+    ("RemoteAccess", "Network"),
+    ("WebBrowser", "Network"),
+    ("FileManager", "System"),
+    ("TerminalEmulator", "System"),
+];
+
+pub fn category_parent(category: &str) -> Option<&'static str> {
+    CATEGORY_PARENTS
+        .iter()
+        .find(|(child, _)| child.eq_ignore_ascii_case(category))
+        .map(|(_, parent)| *parent)
+}
+
+// Categories that don't fit a `pkg_type`. Desktop image formats are launched
+// from the menu as standalone applications, which terminal-only programs and
+// components of the desktop itself aren't.
+pub const PKG_TYPE_INCOHERENT_CATEGORIES: &[(&str, &[&str])] = &[
+    ("appbundle", &["Applet", "ConsoleOnly", "Screensaver"]),
+    ("appimage", &["Applet", "ConsoleOnly", "Screensaver"]),
+    ("flatimage", &["Applet", "ConsoleOnly", "Screensaver"]),
+    ("gameimage", &["Applet", "ConsoleOnly", "Screensaver"]),
+    ("nixappimage", &["Applet", "ConsoleOnly", "Screensaver"]),
+    ("runimage", &["Applet", "ConsoleOnly", "Screensaver"]),
+];
+
+// Whether the category doesn't fit the package type, according to the given
+// `(pkg_type, category)` pairs or `PKG_TYPE_INCOHERENT_CATEGORIES` if None.
+pub fn is_incoherent_category(
+    pkg_type: &str,
+    category: &str,
+    pairs: Option<&[(String, String)]>,
+) -> bool {
+    match pairs {
+        Some(pairs) => pairs
+            .iter()
+            .any(|(t, c)| t == pkg_type && c.eq_ignore_ascii_case(category)),
+        None => PKG_TYPE_INCOHERENT_CATEGORIES
+            .iter()
+            .filter(|(t, _)| *t == pkg_type)
+            .any(|(_, categories)| categories.iter().any(|c| c.eq_ignore_ascii_case(category))),
+    }
+}
+
+// Fields the package type requires. The given formats replace the desktop
+// image formats of `PKG_TYPE_REQUIRED_FIELDS` as the ones needing
+// `GUI_REQUIRED_FIELDS`.
+pub fn required_fields_for_pkg_type(
+    pkg_type: &str,
+    gui_types: Option<&[String]>,
+) -> &'static [&'static str] {
+    match gui_types {
+        Some(gui_types) if gui_types.iter().any(|t| t == pkg_type) => GUI_REQUIRED_FIELDS,
+        Some(_) => &[],
+        None => PKG_TYPE_REQUIRED_FIELDS
+            .iter()
+            .find(|(name, _)| *name == pkg_type)
+            .map_or(&[], |(_, fields)| fields),
+    }
+}
+
+// Describes the fields accepted in an SBUILD as a JSON Schema document.
+pub fn schema() -> serde_json::Value {
+    let properties: serde_json::Map<String, serde_json::Value> = FIELD_VALIDATORS
+        .iter()
+        .map(|validator| (validator.name.to_string(), validator.field_type.schema()))
+        .collect();
+    let required: Vec<&str> = FIELD_VALIDATORS
+        .iter()
+        .filter(|validator| validator.required)
+        .map(|validator| validator.name)
+        .collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "SBUILD",
+        "type": "object",
+        "properties": properties,
+        "required": required
+    })
+}
+
 pub fn is_valid_alpha(value: &str) -> bool {
     value
         .chars()
         .all(|c| c.is_alphanumeric() || c == '+' || c == '-' || c == '_' || c == '.')
 }
 
+// Explains why the identifier can't be used as a file name, which sbuild
+// does with `pkg` and the IDs downstream, or None if it can. Separators and
+// whitespace split or escape the path, and `.` and `..` name directories.
+pub fn path_unsafe_reason(value: &str) -> Option<String> {
+    if let Some(c) = value
+        .chars()
+        .find(|&c| c == '/' || c == '\\' || c.is_whitespace())
+    {
+        return Some(format!("contains {:?}", c));
+    }
+    if value == "." || value == ".." {
+        return Some("names a directory".to_string());
+    }
+    None
+}
+
+// Package naming rules of a distro, by the `distro_pkg` key naming it.
+pub struct DistroNaming {
+    pub distros: &'static [&'static str],
+    // characters allowed besides ASCII letters and digits
+    pub extra_chars: &'static str,
+    pub allow_uppercase: bool,
+    pub min_len: usize,
+    // characters a name can't start with
+    pub invalid_start: &'static str,
+    pub summary: &'static str,
+}
+
+pub const DISTRO_NAMING: &[DistroNaming] = &[
+    DistroNaming {
+        distros: &["debian", "ubuntu"],
+        extra_chars: "+-.",
+        allow_uppercase: false,
+        min_len: 2,
+        invalid_start: "+-.",
+        summary: "at least 2 lowercase letters, digits, +, - or ., starting with a letter or digit",
+    },
+    DistroNaming {
+        distros: &["archlinux", "arch", "aur"],
+        extra_chars: "@._+-",
+        allow_uppercase: false,
+        min_len: 1,
+        invalid_start: "-.",
+        summary: "lowercase letters, digits, @, ., _, + or -, not starting with - or .",
+    },
+    DistroNaming {
+        distros: &["alpine"],
+        extra_chars: "._+-",
+        allow_uppercase: false,
+        min_len: 1,
+        invalid_start: "._+-",
+        summary: "lowercase letters, digits, ., _, + or -, starting with a letter or digit",
+    },
+    DistroNaming {
+        distros: &["fedora", "rhel", "centos"],
+        extra_chars: "._+-",
+        allow_uppercase: true,
+        min_len: 1,
+        invalid_start: "._+-",
+        summary: "letters, digits, ., _, + or -, starting with a letter or digit",
+    },
+    DistroNaming {
+        distros: &["homebrew", "brew"],
+        extra_chars: "@._+-",
+        allow_uppercase: false,
+        min_len: 1,
+        invalid_start: "@._+-",
+        summary: "lowercase letters, digits, @, ., _, + or -, starting with a letter or digit",
+    },
+];
+
+pub fn distro_naming(distro: &str) -> Option<&'static DistroNaming> {
+    DISTRO_NAMING
+        .iter()
+        .find(|naming| naming.distros.contains(&distro))
+}
+
+impl DistroNaming {
+    pub fn is_valid(&self, name: &str) -> bool {
+        name.chars().count() >= self.min_len
+            && !name.starts_with(|c| self.invalid_start.contains(c))
+            && name.chars().all(|c| {
+                c.is_ascii_lowercase()
+                    || c.is_ascii_digit()
+                    || (self.allow_uppercase && c.is_ascii_uppercase())
+                    || self.extra_chars.contains(c)
+            })
+    }
+}
+
+pub fn categories() -> impl Iterator<Item = &'static str> {
+    VALID_CATEGORIES
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+}
+
 pub fn is_valid_category(value: &str) -> bool {
-    VALID_CATEGORIES.lines().any(|line| line.trim() == value)
+    categories().any(|category| category == value)
+}
+
+// Returns the known category matching the value regardless of case.
+pub fn canonical_category(value: &str) -> Option<&'static str> {
+    categories().find(|category| category.eq_ignore_ascii_case(value))
 }
 
 pub fn is_valid_url(value: &str) -> bool {
@@ -1454,3 +1975,272 @@ pub fn is_valid_url(value: &str) -> bool {
         false
     }
 }
+
+// Parses the spellings people commonly use for booleans, including the YAML
+// 1.1 ones that are plain strings in YAML 1.2.
+pub fn parse_boolish(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+// Lowercases the scheme and host of the URL, leaving the path, query and
+// fragment untouched since those can be case-sensitive.
+pub fn normalize_url(value: &str) -> String {
+    match value.split_once("://") {
+        Some((scheme, rest)) => {
+            let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+            let (host, path) = rest.split_at(host_end);
+            format!(
+                "{}://{}{}",
+                scheme.to_lowercase(),
+                host.to_lowercase(),
+                path
+            )
+        }
+        None => value.to_string(),
+    }
+}
+
+// Returns the URL with its host lowercased, or None if the host has no
+// uppercase letters. Unlike `normalize_url` the scheme is left as it is.
+pub fn lowercase_host(value: &str) -> Option<String> {
+    let (scheme, rest) = value.split_once("://")?;
+    let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (host, path) = rest.split_at(host_end);
+    if !host.chars().any(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    Some(format!(
+        "{}://{}{}",
+        scheme,
+        host.to_ascii_lowercase(),
+        path
+    ))
+}
+
+// Returns the lowercased host of the URL, without user info or port.
+pub(crate) fn url_host(value: &str) -> Option<String> {
+    let (_, rest) = value.split_once("://")?;
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host,
+    };
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+// Whether the hosts belong to the same site, taken as sharing their last two
+// labels, so `www.example.org` and `cdn.example.org` match. Suffixes like
+// `co.uk` make this lenient, which is fine for a warning.
+pub fn same_site(host: &str, other: &str) -> bool {
+    let site = |host: &str| -> String {
+        let labels: Vec<&str> = host.trim_end_matches('.').rsplit('.').take(2).collect();
+        labels.join(".")
+    };
+    site(host) == site(other)
+}
+
+const TRACKING_PARAMS: [&str; 10] = [
+    "_ga", "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "msclkid", "yclid", "utm_*",
+];
+
+fn is_tracking_param(param: &str) -> bool {
+    let name = param.split('=').next().unwrap_or(param);
+    TRACKING_PARAMS
+        .iter()
+        .any(|tracking| match tracking.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == *tracking,
+        })
+}
+
+// Returns the URL without its fragment and tracking query parameters, or
+// None if it has neither. Neither is sent to or needed by the server when
+// downloading a source.
+pub fn clean_url(value: &str) -> Option<String> {
+    let (url, fragment) = match value.split_once('#') {
+        Some((url, fragment)) => (url, Some(fragment)),
+        None => (value, None),
+    };
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (url, None),
+    };
+
+    let params: Vec<&str> = query
+        .map(|query| query.split('&').filter(|param| !param.is_empty()).collect())
+        .unwrap_or_default();
+    let kept: Vec<&str> = params
+        .iter()
+        .copied()
+        .filter(|param| !is_tracking_param(param))
+        .collect();
+    if fragment.is_none() && kept.len() == params.len() {
+        return None;
+    }
+
+    if kept.is_empty() {
+        Some(base.to_string())
+    } else {
+        Some(format!("{}?{}", base, kept.join("&")))
+    }
+}
+
+// Whether the URL points at a git repository, i.e. its path ends with `.git`.
+pub fn is_git_url(value: &str) -> bool {
+    let path = value.split(['?', '#']).next().unwrap_or_default();
+    path.trim_end_matches('/').ends_with(".git")
+}
+
+// Returns the `key=value` pin of a git URL, from its fragment or query, whose
+// key is one of `keys`, e.g. `tag=v1.0` for `https://host/repo.git#tag=v1.0`.
+pub fn git_pin<'a, S: AsRef<str>>(value: &'a str, keys: &[S]) -> Option<&'a str> {
+    let (url, fragment) = match value.split_once('#') {
+        Some((url, fragment)) => (url, Some(fragment)),
+        None => (value, None),
+    };
+    let query = url.split_once('?').map(|(_, query)| query);
+
+    query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .chain(fragment)
+        .find(|param| {
+            param.split_once('=').is_some_and(|(key, value)| {
+                !value.is_empty() && keys.iter().any(|k| k.as_ref() == key)
+            })
+        })
+}
+
+// Returns the first placeholder left over from a template: one of `markers`
+// as a whole word, matching case only for uppercase markers like `TODO`, or a
+// `<...>` of several words, e.g. `<description here>`. A single word in angle
+// brackets like `<key>` or `<HOME>` is usually meant literally, and neither
+// are emails and URLs.
+pub fn find_placeholder<'a, S: AsRef<str>>(value: &'a str, markers: &[S]) -> Option<&'a str> {
+    let lowercase = value.to_ascii_lowercase();
+    let is_word_boundary = |index: usize| {
+        value[..index]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric())
+    };
+
+    for marker in markers.iter().map(AsRef::as_ref).filter(|m| !m.is_empty()) {
+        let case_sensitive = marker.chars().any(|c| c.is_uppercase());
+        let (haystack, needle) = if case_sensitive {
+            (value, marker.to_string())
+        } else {
+            (lowercase.as_str(), marker.to_ascii_lowercase())
+        };
+        for (start, _) in haystack.match_indices(&needle) {
+            let end = start + needle.len();
+            let ends_word = value[end..]
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_alphanumeric());
+            if is_word_boundary(start) && ends_word {
+                return Some(&value[start..end]);
+            }
+        }
+    }
+
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let inner = &rest[start + 1..start + len];
+        if inner.chars().any(char::is_alphabetic)
+            && inner.split_whitespace().count() > 1
+            && inner
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-'))
+        {
+            return Some(&rest[start..=start + len]);
+        }
+        rest = &rest[start + 1..];
+    }
+
+    None
+}
+
+// Returns the byte offset and the first character that is a control character
+// (other than a newline) or the U+FFFD replacement character, which usually
+// shows up from mis-encoded copy-paste.
+pub fn find_non_printable(value: &str) -> Option<(usize, char)> {
+    value
+        .char_indices()
+        .find(|(_, c)| (c.is_control() && *c != '\n') || *c == char::REPLACEMENT_CHARACTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lint_string, DEFAULT_PLACEHOLDERS};
+
+    fn markers() -> Vec<&'static str> {
+        DEFAULT_PLACEHOLDERS.split(',').collect()
+    }
+
+    #[test]
+    fn find_placeholder_matches_markers_and_bracketed_phrases() {
+        assert_eq!(find_placeholder("TODO: describe", &markers()), Some("TODO"));
+        assert_eq!(
+            find_placeholder("A <short summary> of the tool", &markers()),
+            Some("<short summary>")
+        );
+        assert_eq!(find_placeholder("Todo list manager", &markers()), None);
+    }
+
+    #[test]
+    fn find_placeholder_ignores_single_bracketed_words() {
+        for value in [
+            "Press <TAB> to complete",
+            "Reads the config from <HOME>/.config",
+            "Run as `tool get <key>`",
+            "Maintained by Jane <jane@example.com>",
+            "See <https://example.com/docs>",
+        ] {
+            assert_eq!(find_placeholder(value, &markers()), None, "{}", value);
+        }
+    }
+
+    fn placeholders_in_note(note: &str) -> Vec<String> {
+        let content = format!(
+            "#!/SBUILD
+_disabled: false
+pkg: hello
+description: Prints a greeting
+note:
+  - {}
+src_url:
+  - https://example.com/hello
+x_exec:
+  shell: sh
+  run: echo hello
+",
+            note
+        );
+        lint_string(&content)
+            .into_iter()
+            .filter(|e| e.rule == "placeholder-text")
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[test]
+    fn single_bracketed_word_in_note_is_not_a_placeholder() {
+        assert!(placeholders_in_note("Run `hello <name>` to greet someone").is_empty());
+        assert_eq!(
+            placeholders_in_note("Run `hello <name>` to greet <your friend here>"),
+            ["'<your friend here>' looks like a placeholder left from a template"]
+        );
+    }
+}
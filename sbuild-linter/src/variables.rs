@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+
+// Variables set by sbuild when running the scripts, each also available in
+// uppercase. The environment is cleared before, so nothing else is inherited
+// apart from `INHERITED_VARIABLES`.
+pub const BUILD_VARIABLES: [&str; 9] = [
+    "pkg",
+    "pkg_id",
+    "pkg_type",
+    "sbuild_pkg",
+    "sbuild_pkgver",
+    "sbuild_outdir",
+    "sbuild_tmpdir",
+    "pkg_ver",
+    "pkgver",
+];
+
+// Passed through from the environment sbuild runs in, if set.
+pub const INHERITED_VARIABLES: [&str; 17] = [
+    "PATH",
+    "DEBIAN_FRONTEND",
+    "EGET_TIMEOUT",
+    "GIT_ASKPASS",
+    "GIT_TERMINAL_PROMPT",
+    "GITHUB_TOKEN",
+    "GH_TOKEN",
+    "GITLAB_TOKEN",
+    "GL_TOKEN",
+    "HF_TOKEN",
+    "HOST_TRIPLET",
+    "NIXPKGS_ALLOW_BROKEN",
+    "NIXPKGS_ALLOW_UNFREE",
+    "NIXPKGS_ALLOW_UNSUPPORTED_SYSTEM",
+    "SYSTMP",
+    "TERM",
+    "USER_AGENT",
+];
+
+// Set by the shell itself.
+const SHELL_VARIABLES: [&str; 26] = [
+    "BASH",
+    "BASHPID",
+    "BASH_REMATCH",
+    "BASH_SOURCE",
+    "BASH_VERSION",
+    "BASH_VERSINFO",
+    "EPOCHREALTIME",
+    "EPOCHSECONDS",
+    "EUID",
+    "FUNCNAME",
+    "GROUPS",
+    "HOSTNAME",
+    "HOSTTYPE",
+    "IFS",
+    "LINENO",
+    "MACHTYPE",
+    "OLDPWD",
+    "OPTARG",
+    "OPTIND",
+    "OSTYPE",
+    "PIPESTATUS",
+    "PPID",
+    "PWD",
+    "RANDOM",
+    "SECONDS",
+    "SHLVL",
+];
+
+// A variable used by a script that is neither provided to it nor assigned in
+// it.
+pub struct Reference {
+    pub name: String,
+    // line within the script, starting at 1
+    pub line: usize,
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn read_name(chars: &[char], start: usize) -> &[char] {
+    let end = chars[start..]
+        .iter()
+        .position(|&c| !is_name_char(c))
+        .map_or(chars.len(), |len| start + len);
+    &chars[start..end]
+}
+
+fn is_provided(name: &str, allowed: &[String]) -> bool {
+    BUILD_VARIABLES
+        .iter()
+        .any(|var| *var == name || var.to_uppercase() == name)
+        || INHERITED_VARIABLES.contains(&name)
+        || SHELL_VARIABLES.contains(&name)
+        || allowed.iter().any(|var| var == name)
+}
+
+// Provided variable differing only in case, to point out the likely typo.
+pub fn suggest(name: &str, allowed: &[String]) -> Option<String> {
+    BUILD_VARIABLES
+        .iter()
+        .map(|var| var.to_string())
+        .chain(INHERITED_VARIABLES.iter().map(|var| var.to_string()))
+        .chain(allowed.iter().cloned())
+        .find(|var| var.eq_ignore_ascii_case(name))
+}
+
+// Names assigned anywhere in the script, regardless of whether the assignment
+// comes before the use, since functions and loops make the order unreliable.
+fn assigned_names(script: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for line in script.lines() {
+        let words: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')'))
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        for (index, word) in words.iter().enumerate() {
+            // NAME=value, NAME+=value and NAME[index]=value, also after
+            // export, local, declare and readonly
+            let name: String = word.chars().take_while(|&c| is_name_char(c)).collect();
+            let rest = &word[name.len()..];
+            if !name.is_empty()
+                && name.starts_with(is_name_start)
+                && (rest.starts_with('=') || rest.starts_with("+=") || rest.starts_with('['))
+            {
+                names.insert(name);
+                continue;
+            }
+
+            let previous = index.checked_sub(1).map(|index| words[index]);
+            let declares = matches!(
+                previous,
+                Some("export" | "local" | "declare" | "typeset" | "readonly")
+            );
+            // `for NAME in`, `read [-r] NAME...`, `getopts SPEC NAME` and
+            // declarations without a value
+            let reads = words[..index]
+                .iter()
+                .rposition(|word| *word == "read" || *word == "mapfile")
+                .is_some_and(|start| {
+                    words[start + 1..index]
+                        .iter()
+                        .all(|word| word.starts_with('-') || is_assignable(word))
+                });
+            if (declares || reads || previous == Some("for") || getopts_name(&words, index))
+                && is_assignable(word)
+            {
+                names.insert(word.to_string());
+            }
+        }
+    }
+    names
+}
+
+fn is_assignable(word: &str) -> bool {
+    word.starts_with(is_name_start) && word.chars().all(is_name_char)
+}
+
+fn getopts_name(words: &[&str], index: usize) -> bool {
+    index >= 2 && words[index - 2] == "getopts"
+}
+
+// Returns the first use of every variable that isn't provided by sbuild or
+// the shell, assigned in the script or listed in `allowed`. Single-quoted
+// strings, comments and quoted heredocs are not expanded by the shell and are
+// skipped. Expansions with a default like `${name:-value}` handle being unset
+// and are not reported either.
+pub fn check(script: &str, allowed: &[String]) -> Vec<Reference> {
+    let mut assigned = assigned_names(script);
+    let mut reported = HashSet::new();
+    let mut references = Vec::new();
+
+    let mut heredoc_end: Option<String> = None;
+    let mut in_double_quotes = false;
+    for (index, line) in script.lines().enumerate() {
+        if let Some(ref end) = heredoc_end {
+            if line.trim_start_matches('\t') == end {
+                heredoc_end = None;
+            }
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 1,
+                '\'' if !in_double_quotes => {
+                    // unterminated quotes continue on the next line, which
+                    // isn't tracked, so the rest of the line is skipped
+                    match chars[i + 1..].iter().position(|&c| c == '\'') {
+                        Some(len) => i += len + 1,
+                        None => break,
+                    }
+                }
+                '"' => in_double_quotes = !in_double_quotes,
+                '#' if !in_double_quotes && (i == 0 || chars[i - 1].is_whitespace()) => break,
+                '<' if !in_double_quotes && chars[i..].starts_with(&['<', '<']) => {
+                    if let Some(end) = quoted_heredoc(&chars[i + 2..]) {
+                        heredoc_end = Some(end);
+                    }
+                    i += 1;
+                }
+                '$' => {
+                    let (name, handles_unset) = match chars.get(i + 1) {
+                        Some('{') => {
+                            let start =
+                                i + 2 + usize::from(matches!(chars.get(i + 2), Some('#' | '!')));
+                            let name = read_name(&chars, start.min(chars.len()));
+                            let operator = chars.get(start + name.len()..).unwrap_or_default();
+                            let operator = operator.strip_prefix(&[':']).unwrap_or(operator);
+                            // `${name:=value}` also assigns the default
+                            if operator.first() == Some(&'=') {
+                                assigned.insert(name.iter().collect());
+                            }
+                            let handles_unset =
+                                matches!(operator.first(), Some('-' | '=' | '+' | '?'));
+                            (name, handles_unset)
+                        }
+                        Some(&c) if is_name_start(c) => (read_name(&chars, i + 1), false),
+                        _ => (&[][..], false),
+                    };
+                    let name: String = name.iter().collect();
+                    if !name.is_empty()
+                        && !handles_unset
+                        && !assigned.contains(&name)
+                        && !is_provided(&name, allowed)
+                        && reported.insert(name.clone())
+                    {
+                        references.push(Reference {
+                            name,
+                            line: index + 1,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    references
+}
+
+// Delimiter of a heredoc whose body isn't expanded, i.e. one written as
+// `<<'EOF'`, `<<"EOF"` or `<<\EOF`, with or without `-`.
+fn quoted_heredoc(chars: &[char]) -> Option<String> {
+    let rest: String = chars.iter().collect();
+    let rest = rest.strip_prefix('-').unwrap_or(&rest).trim_start();
+    let (quote, rest) = match rest.chars().next()? {
+        c @ ('\'' | '"') => (Some(c), &rest[1..]),
+        '\\' => (None, &rest[1..]),
+        _ => return None,
+    };
+    let end = match quote {
+        Some(quote) => rest.find(quote)?,
+        None => rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len()),
+    };
+    Some(rest[..end].to_string())
+}
@@ -0,0 +1,105 @@
+// Checks for line endings and indentation in embedded scripts that behave
+// differently depending on the shell or confuse shellcheck.
+
+pub struct Issue {
+    pub rule: &'static str,
+    // line within the script, starting at 1
+    pub line: usize,
+    pub message: String,
+}
+
+const TAB_WIDTH: usize = 4;
+
+fn indentation(line: &str) -> &str {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    &line[..line.len() - trimmed.len()]
+}
+
+// Returns the first line with a CRLF line ending and the first line whose
+// indentation doesn't match the style used by the rest of the script.
+pub fn check(script: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    if let Some(index) = script.split('\n').position(|line| line.ends_with('\r')) {
+        let count = script.matches("\r\n").count() + usize::from(script.ends_with('\r'));
+        issues.push(Issue {
+            rule: "crlf-line-ending",
+            line: index + 1,
+            message: format!(
+                "Script has {} line(s) ending with CRLF. Use LF line endings",
+                count
+            ),
+        });
+    }
+
+    if let Some(line) = first_mixed_indentation(script) {
+        issues.push(Issue {
+            rule: "mixed-indentation",
+            line,
+            message: "Script mixes tabs and spaces for indentation".to_string(),
+        });
+    }
+
+    issues
+}
+
+// Returns the first line, starting at 1, indented with both tabs and spaces
+// or with a different one than the lines before it.
+fn first_mixed_indentation(text: &str) -> Option<usize> {
+    let mut uses_tabs = None;
+    for (index, line) in text.lines().enumerate() {
+        let indent = indentation(line);
+        if indent.is_empty() {
+            continue;
+        }
+
+        let mixed = indent.contains(' ') && indent.contains('\t');
+        let tabs = indent.contains('\t');
+        if mixed || uses_tabs.is_some_and(|uses_tabs| uses_tabs != tabs) {
+            return Some(index + 1);
+        }
+        uses_tabs.get_or_insert(tabs);
+    }
+    None
+}
+
+// Checks the indentation across the whole document. YAML is indented with
+// spaces, so this catches tabs in the scripts even when each script is
+// consistent on its own.
+pub fn check_file(content: &str) -> Option<Issue> {
+    first_mixed_indentation(content).map(|line| Issue {
+        rule: "mixed-file-indentation",
+        line,
+        message: "File mixes tabs and spaces for indentation".to_string(),
+    })
+}
+
+// Converts line endings to LF and, if tabs and spaces are mixed, indentation
+// to spaces. Scripts using `<<-` heredocs rely on leading tabs being stripped,
+// so their indentation is left as is.
+pub fn normalize(script: &str) -> String {
+    let script = script.replace("\r\n", "\n");
+    let mixed = check(&script)
+        .iter()
+        .any(|issue| issue.rule == "mixed-indentation");
+    if !mixed || script.contains("<<-") {
+        return script;
+    }
+    expand_tabs(&script)
+}
+
+// Converts tabs in the indentation of every line to spaces.
+pub fn expand_tabs(script: &str) -> String {
+    let mut normalized = String::with_capacity(script.len());
+    for line in script.split_inclusive('\n') {
+        let indent = indentation(line);
+        for c in indent.chars() {
+            match c {
+                '\t' => normalized.push_str(&" ".repeat(TAB_WIDTH)),
+                c => normalized.push(c),
+            }
+        }
+        normalized.push_str(&line[indent.len()..]);
+    }
+    normalized
+}
@@ -0,0 +1,339 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use tempfile::TempDir;
+
+const VALID: &str = "#!/SBUILD
+_disabled: false
+pkg: hello
+description: Prints a greeting
+src_url:
+  - https://example.com/hello
+x_exec:
+  shell: sh
+  run: echo hello
+";
+
+const INVALID: &str = "#!/SBUILD
+_disabled: false
+pkg: \"\"
+description: \"   \"
+src_url:
+  - https://example.com/hello
+x_exec:
+  shell: sh
+  run: echo hello
+";
+
+fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn run<P: AsRef<Path>>(args: &[&str], files: &[P]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sbuild-linter"))
+        .args(args)
+        .args(files.iter().map(AsRef::as_ref))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn json_stdout_stays_valid_when_files_fail() {
+    let dir = TempDir::new().unwrap();
+    let files = [
+        write(&dir, "valid.yaml", VALID),
+        write(&dir, "invalid.yaml", INVALID),
+        write(&dir, "garbage.yaml", "#!/SBUILD\n: [\n"),
+        dir.path().join("missing.yaml"),
+    ];
+
+    let output = run(&["--format", "json", "--no-shellcheck"], &files);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"].as_array().unwrap().len(), files.len());
+
+    let output = run(&["--format", "ndjson", "--no-shellcheck"], &files);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for line in stdout.lines() {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+}
+
+#[test]
+fn json_stdout_is_empty_when_the_linter_cannot_start() {
+    let dir = TempDir::new().unwrap();
+    let file = write(&dir, "valid.yaml", VALID);
+
+    // no shellcheck on PATH stops the run before any file is linted
+    let output = Command::new(env!("CARGO_BIN_EXE_sbuild-linter"))
+        .args(["--format", "json"])
+        .arg(&file)
+        .env("PATH", dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+}
+
+#[test]
+fn pkgver_mode_reports_the_same_field_diagnostics() {
+    let dir = TempDir::new().unwrap();
+    let file = write(&dir, "invalid.yaml", INVALID);
+
+    let diagnostics = |args: &[&str]| {
+        let output = run(args, &[&file]);
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        report["files"][0]["diagnostics"].clone()
+    };
+    let default = diagnostics(&["--format", "json", "--no-shellcheck"]);
+    let pkgver = diagnostics(&["--format", "json", "--no-shellcheck", "--pkgver"]);
+    assert!(!default.as_array().unwrap().is_empty());
+    assert_eq!(default, pkgver);
+}
+
+#[test]
+fn unreadable_files_fail_without_stopping_the_run() {
+    let dir = TempDir::new().unwrap();
+    let valid = write(&dir, "valid.yaml", VALID);
+    // a file used as a directory can't be opened whatever the privileges
+    let not_a_dir = valid.join("nested.yaml");
+    let mut files = vec![not_a_dir.clone(), valid.clone()];
+
+    #[cfg(unix)]
+    let denied = {
+        use std::os::unix::fs::PermissionsExt;
+
+        let denied = write(&dir, "denied.yaml", VALID);
+        fs::set_permissions(&denied, fs::Permissions::from_mode(0o000)).unwrap();
+        // root reads the file anyway
+        fs::read(&denied).is_err().then_some(denied)
+    };
+    #[cfg(not(unix))]
+    let denied: Option<PathBuf> = None;
+    files.extend(denied.clone());
+
+    let output = run(&["--format", "json", "--no-shellcheck"], &files);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let result = |path: &Path| {
+        report["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|file| file["path"] == path.to_str().unwrap())
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(result(&valid)["passed"], true);
+    let mut unreadable = vec![not_a_dir];
+    unreadable.extend(denied);
+    for path in unreadable {
+        let file = result(&path);
+        assert_eq!(file["passed"], false);
+        let diagnostic = &file["diagnostics"][0];
+        assert_eq!(diagnostic["rule"], "unreadable-file");
+        let message = diagnostic["message"].as_str().unwrap();
+        assert!(message.contains(path.to_str().unwrap()), "{}", message);
+        assert!(message.contains("os error"), "{}", message);
+    }
+}
+
+fn with_pkgver(script: &str) -> String {
+    VALID.replace(
+        "  run: echo hello",
+        &format!("  pkgver: {}\n  run: echo hello", script),
+    )
+}
+
+#[test]
+fn parallel_rejects_zero_jobs() {
+    let dir = TempDir::new().unwrap();
+    let file = write(&dir, "valid.yaml", VALID);
+
+    let output = run(&["--no-shellcheck", "--parallel", "0"], &[&file]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("must be at least 1"), "{}", stderr);
+
+    let output = run(&["--no-shellcheck", "--parallel", "1"], &[&file]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn parallel_warns_about_absurd_job_counts() {
+    let dir = TempDir::new().unwrap();
+    let file = write(&dir, "valid.yaml", VALID);
+
+    let log = dir.path().join("op.log");
+    let log_arg = log.to_str().unwrap();
+    let output = run(
+        &[
+            "--no-shellcheck",
+            "--parallel",
+            "100000",
+            "--log-file",
+            log_arg,
+        ],
+        &[&file],
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--parallel 100000 is far more than"),
+        "{}",
+        stderr
+    );
+    // only as many workers as there are files are started
+    let log = fs::read_to_string(log).unwrap();
+    assert!(
+        log.contains("linting 1 file(s) with 1 worker(s)"),
+        "{}",
+        log
+    );
+}
+
+#[test]
+fn zero_timeout_waits_for_the_pkgver_script() {
+    let dir = TempDir::new().unwrap();
+    let file = write(&dir, "slow.yaml", &with_pkgver("sleep 2; echo 1.0"));
+
+    let output = run(&["--no-shellcheck", "--pkgver", "--timeout", "0"], &[&file]);
+    assert!(output.status.success());
+
+    let output = run(&["--no-shellcheck", "--pkgver", "--timeout", "1"], &[&file]);
+    assert!(!output.status.success());
+}
+
+// Directory holding a `shellcheck` that finds nothing and logs every run to
+// `calls` next to it.
+#[cfg(unix)]
+fn fake_shellcheck(dir: &TempDir) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let script = bin.join("shellcheck");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\n\
+             if [ \"$1\" = --version ]; then echo 'version: 0.0.0'; exit 0; fi\n\
+             echo \"$@\" >> '{}'\n\
+             echo '{{\"comments\":[]}}'\n",
+            bin.join("calls").display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    bin
+}
+
+#[cfg(unix)]
+#[test]
+fn processes_sharing_a_cache_dir_keep_it_consistent() {
+    let dir = TempDir::new().unwrap();
+    let bin = fake_shellcheck(&dir);
+    let cache_dir = dir.path().join("cache");
+    let files: Vec<PathBuf> = (0..40)
+        .map(|index| {
+            let recipe = VALID.replace("echo hello", &format!("echo hello {}", index));
+            write(&dir, &format!("pkg{}.yaml", index), &recipe)
+        })
+        .collect();
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let linter = |cache_dir: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_sbuild-linter"))
+            .args(["--parallel", "4", "--cache-dir"])
+            .arg(cache_dir)
+            .args(&files)
+            .env("PATH", &path)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap()
+    };
+    let entries = |cache_dir: &Path| {
+        let mut entries: Vec<PathBuf> = fs::read_dir(cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+        entries
+    };
+    let calls = || {
+        fs::read_to_string(bin.join("calls"))
+            .unwrap()
+            .lines()
+            .count()
+    };
+
+    let mut processes = [linter(&cache_dir), linter(&cache_dir)];
+    for process in &mut processes {
+        assert!(process.wait().unwrap().success());
+    }
+
+    // the same entries as a process on its own, all complete and without
+    // temporary files left behind
+    let alone_dir = dir.path().join("alone");
+    assert!(linter(&alone_dir).wait().unwrap().success());
+    let shared = entries(&cache_dir);
+    assert_eq!(
+        shared.iter().map(|e| e.file_name()).collect::<Vec<_>>(),
+        entries(&alone_dir)
+            .iter()
+            .map(|e| e.file_name())
+            .collect::<Vec<_>>()
+    );
+    for entry in &shared {
+        assert_eq!(entry.extension().unwrap(), "json", "{}", entry.display());
+        serde_json::from_slice::<serde_json::Value>(&fs::read(entry).unwrap()).unwrap();
+    }
+
+    // everything is cached now, so a further run doesn't call shellcheck
+    let before = calls();
+    assert!(linter(&cache_dir).wait().unwrap().success());
+    assert_eq!(calls(), before);
+}
+
+#[test]
+fn concurrency_models_agree_on_a_synthetic_batch() {
+    let dir = TempDir::new().unwrap();
+    let files: Vec<PathBuf> = (0..30_usize)
+        .map(|index| {
+            let recipe = if index.is_multiple_of(3) {
+                INVALID
+            } else {
+                VALID
+            };
+            write(&dir, &format!("pkg{}.yaml", index), recipe)
+        })
+        .collect();
+
+    let report = |model: &str| {
+        let args = [
+            "--format",
+            "json",
+            "--no-shellcheck",
+            "--parallel",
+            "4",
+            "--concurrency-model",
+            model,
+        ];
+        let output = run(&args, &files);
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    let threadpool = report("threadpool");
+    assert_eq!(threadpool["files"].as_array().unwrap().len(), files.len());
+    assert_eq!(threadpool, report("rayon"));
+}
@@ -25,12 +25,13 @@ use squishy::{
 use crate::{
     cleanup::Finalize,
     constant::{
-        APPIMAGE_MAGIC_BYTES, ELF_MAGIC_BYTES, FLATIMAGE_MAGIC_BYTES, PNG_MAGIC_BYTES,
-        SVG_MAGIC_BYTES, XML_MAGIC_BYTES,
+        APPIMAGE_MAGIC_BYTES, ELF_MAGIC_BYTES, FLATIMAGE_MAGIC_BYTES, IMAGE_EXTENSIONS,
+        PNG_MAGIC_BYTES,
     },
     types::{OutputStream, PackageType, SoarEnv},
     utils::{
-        calc_magic_bytes, download, extract_filename, is_static_elf, pack_appimage, temp_file,
+        calc_magic_bytes, detect_image_format, download, extract_filename, is_static_elf,
+        pack_appimage, temp_file,
     },
 };
 
@@ -159,7 +160,6 @@ pub struct Builder {
     pkg_type: PackageType,
     log_level: u8,
     keep: bool,
-    timeout: Duration,
 }
 
 impl Builder {
@@ -169,7 +169,9 @@ impl Builder {
         external: bool,
         log_level: u8,
         keep: bool,
-        timeout: Duration,
+        // builds aren't time-limited, the timeout is accepted so callers
+        // passing it keep working
+        _timeout: Duration,
     ) -> Self {
         Builder {
             logger,
@@ -181,7 +183,6 @@ impl Builder {
             pkg_type: PackageType::Unknown,
             log_level,
             keep,
-            timeout,
         }
     }
 
@@ -201,27 +202,24 @@ impl Builder {
 
     pub async fn handle_license(&mut self, licenses: &[License]) {
         for license in licenses {
-            match license {
-                License::Complex(license_complex) => {
-                    if let Some(ref file) = license_complex.file {
-                        let file_path = Path::new(file.trim_start_matches('/'));
-                        if file_path.exists() {
-                            self.logger
-                                .info(format!("Copying license from {} to LICENSE", file));
-                            fs::copy(&file_path, "LICENSE").unwrap();
-                            fs::remove_file(&file_path).unwrap();
-                            return;
-                        }
-                    } else if let Some(ref url) = license_complex.url {
+            if let License::Complex(license_complex) = license {
+                if let Some(ref file) = license_complex.file {
+                    let file_path = Path::new(file.trim_start_matches('/'));
+                    if file_path.exists() {
                         self.logger
-                            .info(format!("Downloading license from {} to LICENSE", url));
-                        if download(url, "LICENSE").await.is_err() {
-                            self.logger
-                                .warn(format!("Failed to download license from {}", url));
-                        };
+                            .info(format!("Copying license from {} to LICENSE", file));
+                        fs::copy(file_path, "LICENSE").unwrap();
+                        fs::remove_file(file_path).unwrap();
+                        return;
                     }
+                } else if let Some(ref url) = license_complex.url {
+                    self.logger
+                        .info(format!("Downloading license from {} to LICENSE", url));
+                    if download(url, "LICENSE").await.is_err() {
+                        self.logger
+                            .warn(format!("Failed to download license from {}", url));
+                    };
                 }
-                _ => {}
             }
         }
     }
@@ -233,17 +231,17 @@ impl Builder {
     ) -> Result<(), String> {
         if let Some(ref desktop) = build_config.desktop {
             let out_path = if let Some(ref file) = desktop.file {
-                self.logger.info(&format!("Using local file from {}", file));
+                self.logger.info(format!("Using local file from {}", file));
                 extract_filename(file)
             } else if let Some(ref dir) = desktop.dir {
                 let out_path = format!("{}/{}.desktop", dir, build_config.pkg);
                 self.logger
-                    .info(&format!("Using local file from {}", out_path));
+                    .info(format!("Using local file from {}", out_path));
                 out_path
             } else {
                 let url = &desktop.url.clone().unwrap();
                 let out_path = extract_filename(url);
-                self.logger.info(&format!(
+                self.logger.info(format!(
                     "Downloading desktop file from {} to {}",
                     url, out_path
                 ));
@@ -257,7 +255,7 @@ impl Builder {
                 fs::rename(out_path, final_path).unwrap();
                 self.desktop.insert(context.pkg.clone(), true);
             } else {
-                self.logger.warn(&format!(
+                self.logger.warn(format!(
                     "Desktop file not found in {}. Skipping...",
                     out_path.display()
                 ));
@@ -266,7 +264,7 @@ impl Builder {
 
         if let Some(ref icon) = build_config.icon {
             let out_path = if let Some(ref file) = icon.file {
-                self.logger.info(&format!("Using local file from {}", file));
+                self.logger.info(format!("Using local file from {}", file));
                 extract_filename(file)
             } else if let Some(ref dir) = icon.dir {
                 let dir_path = Path::new(dir);
@@ -274,14 +272,11 @@ impl Builder {
                 let find_diricon = |dir_path: &Path| -> Result<Option<String>, String> {
                     for entry in fs::read_dir(dir_path)
                         .map_err(|err| format!("Unable to search dir {}: {:#?}", dir, err))?
+                        .flatten()
                     {
-                        if let Ok(entry) = entry {
-                            let path = entry.path();
-                            if path.is_file() {
-                                if path.file_name() == Some(".DirIcon".as_ref()) {
-                                    return Ok(Some(path.to_string_lossy().into_owned()));
-                                }
-                            }
+                        let path = entry.path();
+                        if path.is_file() && path.file_name() == Some(".DirIcon".as_ref()) {
+                            return Ok(Some(path.to_string_lossy().into_owned()));
                         }
                     }
                     Ok(None)
@@ -289,18 +284,16 @@ impl Builder {
 
                 let found_path = find_diricon(dir_path)?.or_else(|| {
                     for extension in ["png", "svg"] {
-                        for entry in fs::read_dir(dir_path).unwrap() {
-                            if let Ok(entry) = entry {
-                                let path = entry.path();
-                                if path.is_file() {
-                                    if let Some(ext) = path
-                                        .extension()
-                                        .and_then(|ext| ext.to_str())
-                                        .map(|s| s.to_lowercase())
-                                    {
-                                        if ext == extension {
-                                            return Some(path.to_string_lossy().into_owned());
-                                        }
+                        for entry in fs::read_dir(dir_path).unwrap().flatten() {
+                            let path = entry.path();
+                            if path.is_file() {
+                                if let Some(ext) = path
+                                    .extension()
+                                    .and_then(|ext| ext.to_str())
+                                    .map(|s| s.to_lowercase())
+                                {
+                                    if ext == extension {
+                                        return Some(path.to_string_lossy().into_owned());
                                     }
                                 }
                             }
@@ -310,8 +303,7 @@ impl Builder {
                 });
 
                 if let Some(found) = found_path {
-                    self.logger
-                        .info(&format!("Using local file from {}", found));
+                    self.logger.info(format!("Using local file from {}", found));
                     found
                 } else {
                     format!("{}/.DirIcon", dir)
@@ -320,37 +312,57 @@ impl Builder {
                 let url = &icon.url.clone().unwrap();
                 let out_path = extract_filename(url);
                 self.logger
-                    .info(&format!("Downloading icon from {} to {}", url, out_path));
+                    .info(format!("Downloading icon from {} to {}", url, out_path));
                 download(url, &out_path).await?;
                 out_path
             };
 
             let out_path = Path::new(&out_path);
             if out_path.exists() {
-                let magic_bytes = calc_magic_bytes(&out_path, 8);
+                let magic_bytes = calc_magic_bytes(out_path, 12);
+                let format = detect_image_format(&magic_bytes);
+
+                // catches icons that were renamed instead of converted
+                let declared = out_path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_lowercase());
+                if let (Some(declared), Some(format)) = (declared, format) {
+                    let declared = match declared.as_str() {
+                        "jpeg" => "jpg",
+                        "svgz" => "svg",
+                        declared => declared,
+                    };
+                    if IMAGE_EXTENSIONS.contains(&declared) && declared != format {
+                        self.logger.warn(format!(
+                            "Icon {} has a .{} extension but is a {} image",
+                            out_path.display(),
+                            declared,
+                            format.to_uppercase()
+                        ));
+                    }
+                }
 
-                if let Some(extension) = if magic_bytes == PNG_MAGIC_BYTES {
-                    Some("png")
-                } else if magic_bytes[..4] == SVG_MAGIC_BYTES || magic_bytes[..5] == XML_MAGIC_BYTES
-                {
-                    Some("svg")
-                } else {
-                    None
-                } {
+                if let Some(extension) = format.filter(|format| matches!(*format, "png" | "svg")) {
                     let final_path = format!("{}.{}", build_config.pkg, extension);
-                    self.logger.info(&format!("Renamed icon to {}", final_path));
+                    self.logger.info(format!("Renamed icon to {}", final_path));
                     fs::rename(out_path, final_path).unwrap();
                     self.icon.insert(context.pkg.clone(), true);
                 } else {
                     let tmp_path = context.tmpdir.join(out_path);
-                    fs::rename(&out_path, &tmp_path).unwrap();
-                    self.logger.warn(&format!(
-                        "Unsupported icon. Moved to {}",
-                        tmp_path.display()
-                    ));
+                    fs::rename(out_path, &tmp_path).unwrap();
+                    match format {
+                        Some(format) => self.logger.warn(format!(
+                            "Unsupported icon format {}. Moved to {}",
+                            format.to_uppercase(),
+                            tmp_path.display()
+                        )),
+                        None => self
+                            .logger
+                            .warn(format!("Unsupported icon. Moved to {}", tmp_path.display())),
+                    }
                 }
             } else {
-                self.logger.warn(&format!(
+                self.logger.warn(format!(
                     "Icon not found in {}. Skipping...",
                     out_path.display()
                 ));
@@ -486,7 +498,7 @@ impl Builder {
             }
         }
 
-        self.handle_provides(&context, &build_config);
+        self.handle_provides(context, &build_config);
 
         let mut finalize = Finalize::new(
             &context.outdir,
@@ -518,7 +530,7 @@ impl Builder {
         let version_file = format!("{}.pkgver", file_path);
 
         if let Some(build_config) = linter.lint(file_path, false, false, true) {
-            logger.info(format!("{}", fs::read_to_string(&validated_file).unwrap()));
+            logger.info(fs::read_to_string(&validated_file).unwrap().to_string());
             if build_config._disabled {
                 logger.error(format!("{} -> Disabled package. Skipping...", file_path));
                 if let Some(reason) = build_config._disabled_reason {
@@ -561,14 +573,14 @@ impl Builder {
 
                 if let Some(ref arch) = x_exec.arch {
                     if !arch.contains(&ARCH.to_string()) {
-                        logger.error(format!("Unsupported architecture. Aborting..."));
+                        logger.error("Unsupported architecture. Aborting...".to_string());
                         return false;
                     }
                 }
 
                 if let Some(ref arch) = x_exec.os {
                     if !arch.contains(&OS.to_string()) {
-                        logger.error(format!("Unsupported OS. Aborting..."));
+                        logger.error("Unsupported OS. Aborting...".to_string());
                         return false;
                     }
                 }
@@ -576,7 +588,7 @@ impl Builder {
                 if let Some(ref host) = x_exec.host {
                     let current_host = format!("{ARCH}-{OS}");
                     if !host.contains(&current_host) {
-                        logger.error(format!("Unsupported HOST. Aborting..."));
+                        logger.error("Unsupported HOST. Aborting...".to_string());
                         return false;
                     }
                 }
@@ -590,7 +602,7 @@ impl Builder {
                         context.outdir.display()
                     ));
                 } else {
-                    logger.success(&format!("Failed to build the package: {}", context.pkg));
+                    logger.success(format!("Failed to build the package: {}", context.pkg));
                 }
             }
         } else {
@@ -619,7 +631,7 @@ impl Builder {
 
         for provide in provides {
             let cmd = provide
-                .split_once(|c| c == ':' || c == '=')
+                .split_once([':', '='])
                 .map(|(p1, _)| p1.to_string())
                 .unwrap_or_else(|| provide.to_string());
             let provide_path = Path::new(&cmd);
@@ -632,7 +644,7 @@ impl Builder {
 
             exists_any = true;
 
-            let magic_bytes = calc_magic_bytes(&provide_path, 12);
+            let magic_bytes = calc_magic_bytes(provide_path, 12);
 
             if magic_bytes[4] != 2 {
                 self.logger
@@ -649,9 +661,9 @@ impl Builder {
                     None
                 };
 
-                let offset = get_offset(&provide_path).unwrap();
+                let offset = get_offset(provide_path).unwrap();
 
-                if !is_static_elf(&provide_path) {
+                if !is_static_elf(provide_path) {
                     self.logger.info(format!(
                         "{} -> Dynamic AppImage. Attempting to convert it to static.",
                         &provide_path.display()
@@ -687,7 +699,7 @@ impl Builder {
                     if !Path::new(tmp_path).exists() {
                         self.logger.warn("Failed to unpack appimage");
                     }
-                    if pack_appimage(env_vars, tmp_path, &file_path, &self.logger) {
+                    if pack_appimage(env_vars, tmp_path, file_path, &self.logger) {
                         self.logger.info(format!(
                             "{} -> Successfully converted to static AppImage.",
                             &provide_path.display()
@@ -704,12 +716,12 @@ impl Builder {
                 };
                 let squashfs = &appimage.squashfs;
 
-                if self.icon.get(&provide).is_none() {
+                if !self.icon.contains_key(&provide) {
                     if let Some(entry) = appimage.find_icon() {
                         if let EntryKind::File(basic_file) = entry.kind {
                             let dest = format!("{}.DirIcon", cmd);
                             let _ = squashfs.write_file(basic_file, &dest);
-                            self.logger.info(&format!(
+                            self.logger.info(format!(
                                 "Extracted {} to {}",
                                 entry.path.display(),
                                 dest
@@ -725,17 +737,17 @@ impl Builder {
                             fs::rename(&dest, &final_path).unwrap();
 
                             self.logger
-                                .info(&format!("Renamed {} to {}", dest, final_path));
+                                .info(format!("Renamed {} to {}", dest, final_path));
                             self.icon.insert(provide.clone(), true);
                         }
                     }
                 }
-                if self.desktop.get(&provide).is_none() {
+                if !self.desktop.contains_key(&provide) {
                     if let Some(entry) = appimage.find_desktop() {
                         if let EntryKind::File(basic_file) = entry.kind {
                             let dest = format!("{}.desktop", cmd);
                             let _ = squashfs.write_file(basic_file, &dest);
-                            self.logger.info(&format!(
+                            self.logger.info(format!(
                                 "Extracted {} to {}",
                                 entry.path.display(),
                                 dest
@@ -744,7 +756,7 @@ impl Builder {
                         }
                     };
                 }
-                if self.appstream.get(&provide).is_none() {
+                if !self.appstream.contains_key(&provide) {
                     if let Some(entry) = appimage.find_appstream() {
                         if let EntryKind::File(basic_file) = entry.kind {
                             let file_name = if entry
@@ -760,7 +772,7 @@ impl Builder {
                             };
                             let dest = format!("{}.{}.xml", cmd, file_name);
                             let _ = squashfs.write_file(basic_file, &dest);
-                            self.logger.info(&format!(
+                            self.logger.info(format!(
                                 "Extracted {} to {}",
                                 entry.path.display(),
                                 dest
@@ -772,7 +784,7 @@ impl Builder {
             } else if magic_bytes[4..8] == FLATIMAGE_MAGIC_BYTES {
                 self.pkg_type = PackageType::FlatImage
             } else if magic_bytes[..4] == ELF_MAGIC_BYTES {
-                self.pkg_type = if is_static_elf(&provide_path) {
+                self.pkg_type = if is_static_elf(provide_path) {
                     PackageType::Static
                 } else {
                     PackageType::Dynamic
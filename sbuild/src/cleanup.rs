@@ -61,7 +61,7 @@ impl Finalize {
 
         for provide in provides {
             let cmd = provide
-                .split_once(|c| c == ':' || c == '=')
+                .split_once([':', '='])
                 .map(|(p1, _)| p1.to_string())
                 .unwrap_or_else(|| provide.to_string());
 
@@ -87,7 +87,7 @@ impl Finalize {
                 fs::copy(fallback_icon, png_path)?;
             } else {
                 let url = "https://raw.githubusercontent.com/pkgforge/soarpkgs/main/assets/pkg.png";
-                download(&url, &png_path).await.unwrap();
+                download(url, &png_path).await.unwrap();
                 self.fallback_icon = Some(png_path);
             }
         }
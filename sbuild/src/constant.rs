@@ -5,6 +5,15 @@ pub const FLATIMAGE_MAGIC_BYTES: [u8; 4] = [0x46, 0x49, 0x01, 0x00];
 pub const PNG_MAGIC_BYTES: [u8; 8] = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
 pub const SVG_MAGIC_BYTES: [u8; 4] = [0x3c, 0x73, 0x76, 0x67];
 pub const XML_MAGIC_BYTES: [u8; 5] = [0x3c, 0x3f, 0x78, 0x6d, 0x6c];
+pub const JPEG_MAGIC_BYTES: [u8; 3] = [0xff, 0xd8, 0xff];
+pub const GIF_MAGIC_BYTES: [u8; 4] = [0x47, 0x49, 0x46, 0x38];
+// "RIFF", followed by the size and "WEBP"
+pub const RIFF_MAGIC_BYTES: [u8; 4] = [0x52, 0x49, 0x46, 0x46];
+pub const WEBP_MAGIC_BYTES: [u8; 4] = [0x57, 0x45, 0x42, 0x50];
+pub const ICO_MAGIC_BYTES: [u8; 4] = [0x00, 0x00, 0x01, 0x00];
+pub const BMP_MAGIC_BYTES: [u8; 2] = [0x42, 0x4d];
+// formats returned by `detect_image_format`
+pub const IMAGE_EXTENSIONS: [&str; 7] = ["png", "svg", "jpg", "gif", "webp", "ico", "bmp"];
 
 pub const MIN_ICON_SIZE: u64 = 20;
 pub const MIN_DESKTOP_SIZE: u64 = 8;
@@ -176,7 +176,7 @@ async fn main() {
     let logger_handle = thread::spawn(move || {
         while let Ok(log) = rx.recv() {
             match log {
-                LogMessage::Info(msg) => {
+                LogMessage::Heading(msg) | LogMessage::Info(msg) => {
                     println!("{}", msg);
                 }
                 LogMessage::Error(msg) => {
@@ -191,6 +191,12 @@ async fn main() {
                 LogMessage::CustomError(msg) => {
                     eprintln!("{}", msg);
                 }
+                LogMessage::Result(msg) => {
+                    println!("{}", msg);
+                }
+                LogMessage::Dump(msg) => {
+                    eprintln!("{}", msg);
+                }
                 LogMessage::Done => break,
             }
         }
@@ -13,6 +13,11 @@ use memmap2::Mmap;
 use reqwest::header::USER_AGENT;
 use sbuild_linter::logger::TaskLogger;
 
+use crate::constant::{
+    BMP_MAGIC_BYTES, GIF_MAGIC_BYTES, ICO_MAGIC_BYTES, JPEG_MAGIC_BYTES, PNG_MAGIC_BYTES,
+    RIFF_MAGIC_BYTES, SVG_MAGIC_BYTES, WEBP_MAGIC_BYTES, XML_MAGIC_BYTES,
+};
+
 pub async fn download<P: AsRef<Path>>(url: &str, out: P) -> Result<(), String> {
     let client = reqwest::Client::new();
     let response = client
@@ -96,6 +101,31 @@ pub fn calc_magic_bytes<P: AsRef<Path>>(file_path: P, size: usize) -> Vec<u8> {
     magic_bytes
 }
 
+// Format of an image, as the extension it is usually saved with, detected
+// from its first 12 bytes.
+pub fn detect_image_format(magic_bytes: &[u8]) -> Option<&'static str> {
+    if magic_bytes.starts_with(&PNG_MAGIC_BYTES) {
+        Some("png")
+    } else if magic_bytes.starts_with(&SVG_MAGIC_BYTES) || magic_bytes.starts_with(&XML_MAGIC_BYTES)
+    {
+        Some("svg")
+    } else if magic_bytes.starts_with(&JPEG_MAGIC_BYTES) {
+        Some("jpg")
+    } else if magic_bytes.starts_with(&GIF_MAGIC_BYTES) {
+        Some("gif")
+    } else if magic_bytes.starts_with(&RIFF_MAGIC_BYTES)
+        && magic_bytes.get(8..12) == Some(&WEBP_MAGIC_BYTES[..])
+    {
+        Some("webp")
+    } else if magic_bytes.starts_with(&ICO_MAGIC_BYTES) {
+        Some("ico")
+    } else if magic_bytes.starts_with(&BMP_MAGIC_BYTES) {
+        Some("bmp")
+    } else {
+        None
+    }
+}
+
 pub fn calc_checksum<P: AsRef<Path>>(file_path: P) -> String {
     let mut file = File::open(&file_path).unwrap();
     let mut hasher = blake3::Hasher::new();
@@ -148,8 +178,8 @@ pub fn pack_appimage<P: AsRef<Path>>(
             "--mksquashfs-opt",
             "22",
             "--no-appstream",
-            &path.as_ref().to_string_lossy().to_string(),
-            &output_path.as_ref().to_string_lossy().to_string(),
+            path.as_ref().to_string_lossy().as_ref(),
+            output_path.as_ref().to_string_lossy().as_ref(),
         ])
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())